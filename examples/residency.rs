@@ -0,0 +1,29 @@
+use mmap_file::MmapFile;
+
+use std::{env, process};
+
+fn main() {
+	let path = match env::args().nth(1) {
+		Some(path) => path,
+		None => {
+			eprintln!("usage: residency <path>");
+			process::exit(1);
+		}
+	};
+
+	let result = unsafe { MmapFile::open(&path) }.and_then(|file| file.residency());
+
+	match result {
+		Ok(residency) => println!(
+			"{}: {}/{} pages resident, {:.2}%",
+			path,
+			residency.resident_pages(),
+			residency.total_pages(),
+			residency.ratio() * 100.0
+		),
+		Err(e) => {
+			eprintln!("{}: {}", path, e);
+			process::exit(1);
+		}
+	}
+}
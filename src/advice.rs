@@ -0,0 +1,81 @@
+use crate::{page_aligned, MapMode, MmappedFile};
+
+use std::{io, ops::Range};
+
+// access pattern hints passed to `madvise`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Advice {
+	Normal,
+	Sequential,
+	Random,
+	WillNeed,
+	// drops the pages, they are read back in from the file on next access
+	DontNeed,
+	// prefaults the pages for reading, requires Linux 5.14
+	#[cfg(target_os = "linux")]
+	PopulateRead,
+	// prefaults the pages for writing, requires Linux 5.14 and a writable map, on a shared map
+	// this dirties every page and allocates blocks for holes in the file
+	#[cfg(target_os = "linux")]
+	PopulateWrite,
+	// enables transparent huge pages for the range
+	#[cfg(target_os = "linux")]
+	HugePage,
+	#[cfg(target_os = "linux")]
+	NoHugePage,
+}
+
+impl Advice {
+	fn as_raw(self) -> libc::c_int {
+		match self {
+			Advice::Normal => libc::MADV_NORMAL,
+			Advice::Sequential => libc::MADV_SEQUENTIAL,
+			Advice::Random => libc::MADV_RANDOM,
+			Advice::WillNeed => libc::MADV_WILLNEED,
+			Advice::DontNeed => libc::MADV_DONTNEED,
+			#[cfg(target_os = "linux")]
+			Advice::PopulateRead => libc::MADV_POPULATE_READ,
+			#[cfg(target_os = "linux")]
+			Advice::PopulateWrite => libc::MADV_POPULATE_WRITE,
+			#[cfg(target_os = "linux")]
+			Advice::HugePage => libc::MADV_HUGEPAGE,
+			#[cfg(target_os = "linux")]
+			Advice::NoHugePage => libc::MADV_NOHUGEPAGE,
+		}
+	}
+}
+
+impl<M> MmappedFile<M>
+where
+	M: MapMode,
+{
+	pub fn advise(&self, advice: Advice) -> io::Result<()> {
+		self.advise_range(0..self.map.len(), advice)
+	}
+
+	// `range` is widened to the enclosing pages, as madvise only works on whole pages
+	pub fn advise_range(&self, range: Range<usize>, advice: Advice) -> io::Result<()> {
+		// dropping the pages of a private map discards the writes made to it, which would change
+		// bytes under any slice borrowed from it
+		if M::PRIVATE && advice == Advice::DontNeed {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"cannot discard the pages of a copy-on-write map through a shared reference",
+			));
+		}
+
+		madvise(&self.map, range, advice)
+	}
+}
+
+pub(crate) fn madvise(map: &[u8], range: Range<usize>, advice: Advice) -> io::Result<()> {
+	let (ptr, len) = page_aligned(map, range)?;
+
+	log::trace!("madvise {:?} on {} bytes", advice, len);
+
+	if unsafe { libc::madvise(ptr, len, advice.as_raw()) } != 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::MmappedFile;
+
+use memmap::{Mmap, MmapMut};
+
+use std::{fs::File, io};
+
+impl MmappedFile<Mmap> {
+	// mprotects the map writable if the file was opened for writing, otherwise the file is
+	// reopened read-write and remapped over the same range
+	pub fn make_mut(self) -> io::Result<MmappedFile<MmapMut>> {
+		let MmappedFile { file, map, offset } = self;
+
+		if is_writable(&file)? {
+			let map = map.make_mut()?;
+			return Ok(MmappedFile { file, map, offset });
+		}
+
+		log::debug!("file was opened read-only, reopening it to make the map writable");
+
+		let file = reopen_writable(&file)?;
+		let map = unsafe {
+			memmap::MmapOptions::new()
+				.offset(offset)
+				.len(map.len())
+				.map_mut(&file)
+		}?;
+
+		Ok(MmappedFile { file, map, offset })
+	}
+}
+
+impl MmappedFile<MmapMut> {
+	// mprotects the map read-only, the file stays open for writing so `make_mut` is cheap
+	pub fn make_read_only(self) -> io::Result<MmappedFile<Mmap>> {
+		let MmappedFile { file, map, offset } = self;
+		let map = map.make_read_only()?;
+
+		Ok(MmappedFile { file, map, offset })
+	}
+}
+
+#[cfg(unix)]
+fn is_writable(file: &File) -> io::Result<bool> {
+	use std::os::unix::io::AsRawFd;
+
+	let flags = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETFL) };
+
+	if flags < 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	Ok(flags & libc::O_ACCMODE == libc::O_RDWR)
+}
+
+#[cfg(not(unix))]
+fn is_writable(_file: &File) -> io::Result<bool> {
+	Ok(false)
+}
+
+// reopening through /proc keeps working even if the file was renamed or unlinked
+#[cfg(target_os = "linux")]
+fn reopen_writable(file: &File) -> io::Result<File> {
+	use std::{fs::OpenOptions, os::unix::io::AsRawFd};
+
+	OpenOptions::new()
+		.read(true)
+		.write(true)
+		.open(format!("/proc/self/fd/{}", file.as_raw_fd()))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reopen_writable(_file: &File) -> io::Result<File> {
+	Err(io::Error::new(
+		io::ErrorKind::PermissionDenied,
+		"file was opened read-only and can't be reopened for writing",
+	))
+}
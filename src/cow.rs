@@ -0,0 +1,88 @@
+use crate::{options::sealed::Sealed, MapMode, MmapFile, MmapOptions, MmappedFile};
+
+use memmap::{Mmap, MmapMut};
+
+use std::{
+	fs::File,
+	io,
+	ops::{Deref, DerefMut},
+	path::Path,
+};
+
+// a private copy-on-write map, writes to it are only ever visible to this process and are
+// never persisted to the underlying file
+pub struct MmapCow(MmapMut);
+
+pub type MmapCowFile = MmappedFile<MmapCow>;
+
+impl MapMode for MmapCow {}
+
+impl Sealed for MmapCow {
+	// the file itself is only ever read
+	const WRITABLE: bool = false;
+	const PRIVATE: bool = true;
+
+	unsafe fn map(options: &memmap::MmapOptions, file: &File) -> io::Result<Self> {
+		options.map_copy(file).map(MmapCow)
+	}
+}
+
+impl MmapOptions<Mmap> {
+	pub fn copy_on_write(self) -> MmapOptions<MmapCow> {
+		self.with_mode()
+	}
+}
+
+impl MmapFile {
+	pub unsafe fn open_private<P: AsRef<Path>>(path: P) -> io::Result<MmapCowFile> {
+		Self::options().copy_on_write().open(path)
+	}
+}
+
+impl MmapCowFile {
+	// there is nothing to flush, changes to a copy-on-write map never reach the file
+	pub fn flush(&self) -> io::Result<()> {
+		Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"copy-on-write maps are never written back to the file",
+		))
+	}
+}
+
+impl Deref for MmapCow {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl DerefMut for MmapCow {
+	fn deref_mut(&mut self) -> &mut [u8] {
+		&mut self.0
+	}
+}
+
+impl AsRef<[u8]> for MmapCow {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl AsMut<[u8]> for MmapCow {
+	fn as_mut(&mut self) -> &mut [u8] {
+		&mut self.0
+	}
+}
+
+impl DerefMut for MmappedFile<MmapCow> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.map.deref_mut()
+	}
+}
+
+impl AsMut<[u8]> for MmappedFile<MmapCow> {
+	fn as_mut(&mut self) -> &mut [u8] {
+		self.map.as_mut()
+	}
+}
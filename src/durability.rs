@@ -0,0 +1,13 @@
+// what `MmappedWriter::flush` guarantees once it returns `Ok`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Durability {
+	// don't flush at all, leave write back entirely to the kernel
+	None,
+	// schedule the dirty pages for write back without waiting on them
+	#[default]
+	Async,
+	// msync the dirty pages and wait for the write back to complete
+	Sync,
+	// msync, then fsync the file so its metadata (e.g. the length) is durable too
+	SyncAll,
+}
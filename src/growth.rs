@@ -0,0 +1,30 @@
+use std::cmp;
+
+// how a `MmappedWriter` grows its file when a write runs past the end of the map
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GrowthPolicy {
+	// double the map length
+	#[default]
+	Doubling,
+	// grow the map by a fixed number of bytes
+	Increment(usize),
+	// double the map length, but never grow by more than the given number of bytes at once
+	CappedDoubling(usize),
+	// grow the map to exactly fit the pending write
+	ExactFit,
+}
+
+impl GrowthPolicy {
+	// the new map length for a map of `current` bytes that must hold at least `required` bytes,
+	// never less than `required` so a single resize always fits the pending write
+	pub fn next_len(self, current: usize, required: usize) -> usize {
+		let grown = match self {
+			GrowthPolicy::Doubling => current.saturating_mul(2),
+			GrowthPolicy::Increment(n) => current.saturating_add(n),
+			GrowthPolicy::CappedDoubling(cap) => current.saturating_add(cmp::min(current, cap)),
+			GrowthPolicy::ExactFit => required,
+		};
+
+		cmp::max(grown, required)
+	}
+}
@@ -0,0 +1,554 @@
+use crate::{
+	capacity_overflow, invalid_data,
+	pod::{bytes_of_slice, cast_slice, cast_slice_mut},
+	MmapFile, MmapMutFile, Pod,
+};
+
+use std::{
+	convert::TryFrom,
+	ffi::OsString,
+	fs, io,
+	marker::PhantomData,
+	mem,
+	ops::Range,
+	path::{Path, PathBuf},
+	slice,
+};
+
+const MAGIC: u64 = u64::from_le_bytes(*b"MMAPHASH");
+const VERSION: u64 = 1;
+const DEFAULT_CAPACITY: usize = 64;
+const MIN_SLOTS: usize = 8;
+
+// magic, version, key size, value size, slot count, length, reserved
+type Header = [u64; 8];
+
+const EMPTY: u8 = 0;
+const OCCUPIED: u8 = 1;
+
+// where each region lives in the file, the slot states, keys and values are stored as
+// separate arrays so neither `K` nor `V` needs to be padded
+#[derive(Clone, Copy, Debug)]
+struct Layout {
+	slots: usize,
+	states: usize,
+	keys: usize,
+	values: usize,
+	size: usize,
+}
+
+impl Layout {
+	fn new<K: Pod, V: Pod>(slots: usize) -> io::Result<Self> {
+		let states = mem::size_of::<Header>();
+		let keys = align_up(checked_add(states, slots)?, mem::align_of::<K>())?;
+		let values = align_up(
+			checked_add(keys, checked_mul(slots, mem::size_of::<K>())?)?,
+			mem::align_of::<V>(),
+		)?;
+		let size = checked_add(values, checked_mul(slots, mem::size_of::<V>())?)?;
+
+		Ok(Self {
+			slots,
+			states,
+			keys,
+			values,
+			size,
+		})
+	}
+
+	// enough slots to hold `capacity` entries below the maximum load factor
+	fn for_capacity<K: Pod, V: Pod>(capacity: usize) -> io::Result<Self> {
+		let slots = checked_mul(capacity, 4)? / 3 + 1;
+		let slots = slots
+			.checked_next_power_of_two()
+			.ok_or_else(capacity_overflow)?;
+
+		Self::new::<K, V>(slots.max(MIN_SLOTS))
+	}
+
+	// entries that fit before growing, at a load factor of 3/4
+	fn capacity(&self) -> usize {
+		self.slots / 4 * 3
+	}
+}
+
+struct Table<'a, K, V> {
+	states: &'a [u8],
+	keys: &'a [K],
+	values: &'a [V],
+}
+
+impl<'a, K: Pod, V: Pod> Table<'a, K, V> {
+	fn new(map: &'a [u8], layout: &Layout) -> Self {
+		let (states, keys, values) = regions::<K>(layout);
+
+		Self {
+			states: &map[states],
+			keys: cast_slice(&map[keys]).expect("MmapHashMap keys are always aligned"),
+			values: cast_slice(&map[values]).expect("MmapHashMap values are always aligned"),
+		}
+	}
+
+	// the slot holding `key`, or the empty slot where it would be inserted, `None` if every
+	// slot is occupied, which only a corrupt file can cause as the map grows before that
+	fn find(&self, key: &K) -> Result<usize, Option<usize>> {
+		let mask = self.states.len() - 1;
+		let mut slot = hash(key) as usize & mask;
+
+		for _ in 0..self.states.len() {
+			if self.states[slot] == EMPTY {
+				return Err(Some(slot));
+			}
+
+			if key_eq(&self.keys[slot], key) {
+				return Ok(slot);
+			}
+
+			slot = (slot + 1) & mask;
+		}
+
+		Err(None)
+	}
+
+	fn get(&self, key: &K) -> Option<&'a V> {
+		self.find(key).ok().map(|slot| &self.values[slot])
+	}
+
+	fn iter(&self) -> impl Iterator<Item = (&'a K, &'a V)> + 'a {
+		let (states, keys, values) = (self.states, self.keys, self.values);
+
+		states
+			.iter()
+			.enumerate()
+			.filter(|&(_, &state)| state == OCCUPIED)
+			.map(move |(slot, _)| (&keys[slot], &values[slot]))
+	}
+}
+
+struct TableMut<'a, K, V> {
+	states: &'a mut [u8],
+	keys: &'a mut [K],
+	values: &'a mut [V],
+}
+
+impl<'a, K: Pod, V: Pod> TableMut<'a, K, V> {
+	fn new(map: &'a mut [u8], layout: &Layout) -> Self {
+		let (states, keys, values) = regions::<K>(layout);
+
+		let (head, values_bytes) = map.split_at_mut(values.start);
+		let (head, keys_bytes) = head.split_at_mut(keys.start);
+
+		Self {
+			states: &mut head[states],
+			keys: cast_slice_mut(&mut keys_bytes[..keys.len()])
+				.expect("MmapHashMap keys are always aligned"),
+			values: cast_slice_mut(&mut values_bytes[..values.len()])
+				.expect("MmapHashMap values are always aligned"),
+		}
+	}
+
+	fn as_table(&self) -> Table<'_, K, V> {
+		Table {
+			states: self.states,
+			keys: self.keys,
+			values: self.values,
+		}
+	}
+
+	// the caller makes sure there is a free slot
+	fn insert(&mut self, key: K, value: V) -> io::Result<Option<V>> {
+		match self.as_table().find(&key) {
+			Ok(slot) => Ok(Some(mem::replace(&mut self.values[slot], value))),
+			Err(Some(slot)) => {
+				// the entry is marked occupied only once it is written, though readers mapping
+				// the same file still get no ordering guarantee, see `MmapHashMapView`
+				self.keys[slot] = key;
+				self.values[slot] = value;
+				self.states[slot] = OCCUPIED;
+				Ok(None)
+			}
+			Err(None) => Err(invalid_data("MmapHashMap has no free slot, the file is corrupt")),
+		}
+	}
+
+	// backward shift deletion, so lookups never need tombstones
+	fn remove(&mut self, key: &K) -> Option<V> {
+		let mut hole = self.as_table().find(key).ok()?;
+		let value = self.values[hole];
+
+		let mask = self.states.len() - 1;
+		let mut slot = hole;
+
+		// bounded like `find`, in case a corrupt file has no empty slot to stop at
+		for _ in 1..self.states.len() {
+			slot = (slot + 1) & mask;
+
+			if self.states[slot] == EMPTY {
+				break;
+			}
+
+			// entries whose home lies cyclically in (hole, slot] must stay where they are
+			let home = hash(&self.keys[slot]) as usize & mask;
+			let stays = if hole <= slot {
+				hole < home && home <= slot
+			} else {
+				hole < home || home <= slot
+			};
+
+			if !stays {
+				self.keys[hole] = self.keys[slot];
+				self.values[hole] = self.values[slot];
+				hole = slot;
+			}
+		}
+
+		self.states[hole] = EMPTY;
+
+		Some(value)
+	}
+}
+
+// a persistent open-addressing hash map with linear probing, stored in a single file
+pub struct MmapHashMap<K: Pod, V: Pod> {
+	inner: MmapMutFile,
+	path: PathBuf,
+	layout: Layout,
+	len: usize,
+	types: PhantomData<(K, V)>,
+}
+
+impl<K: Pod, V: Pod> MmapHashMap<K, V> {
+	pub unsafe fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		Self::with_capacity(path, DEFAULT_CAPACITY)
+	}
+
+	// creates the file, replacing any existing one
+	pub unsafe fn with_capacity<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Self> {
+		Self::create_with_layout(path.as_ref(), Layout::for_capacity::<K, V>(capacity)?)
+	}
+
+	unsafe fn create_with_layout(path: &Path, layout: Layout) -> io::Result<Self> {
+		if mem::size_of::<K>() == 0 || mem::size_of::<V>() == 0 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"zero-sized types can't be stored in a MmapHashMap",
+			));
+		}
+
+		let inner = MmapFile::options()
+			.read_write()
+			.create(true)
+			.truncate(true)
+			.size(layout.size as u64)
+			.open(path)?;
+
+		let mut map = Self {
+			inner,
+			path: path.to_owned(),
+			layout,
+			len: 0,
+			types: PhantomData,
+		};
+
+		*map.header_mut() = new_header::<K, V>(layout.slots);
+
+		Ok(map)
+	}
+
+	pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let path = path.as_ref();
+		let inner = MmapMutFile::open_rw(path)?;
+		let (layout, len) = read_header::<K, V>(&inner)?;
+
+		Ok(Self {
+			inner,
+			path: path.to_owned(),
+			layout,
+			len,
+			types: PhantomData,
+		})
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn capacity(&self) -> usize {
+		self.layout.capacity()
+	}
+
+	pub fn get(&self, key: &K) -> Option<&V> {
+		self.table().get(key)
+	}
+
+	pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+		let table = self.table_mut();
+		let slot = table.as_table().find(key).ok()?;
+
+		Some(&mut table.values[slot])
+	}
+
+	pub fn contains_key(&self, key: &K) -> bool {
+		self.get(key).is_some()
+	}
+
+	pub fn insert(&mut self, key: K, value: V) -> io::Result<Option<V>> {
+		if self.len == self.capacity() && !self.contains_key(&key) {
+			self.grow()?;
+		}
+
+		let old = self.table_mut().insert(key, value)?;
+
+		if old.is_none() {
+			self.set_len(self.len + 1);
+		}
+
+		Ok(old)
+	}
+
+	pub fn remove(&mut self, key: &K) -> Option<V> {
+		let value = self.table_mut().remove(key)?;
+		self.set_len(self.len - 1);
+
+		Some(value)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+		self.table().iter()
+	}
+
+	pub fn flush(&self) -> io::Result<()> {
+		self.inner.flush()
+	}
+
+	pub fn sync_all(&self) -> io::Result<()> {
+		self.inner.sync_all()
+	}
+
+	// rehashes into a new file twice the size, which atomically replaces the old one, so views
+	// that still have the old file open keep it as a snapshot
+	fn grow(&mut self) -> io::Result<()> {
+		let slots = self.layout.slots.checked_mul(2).ok_or_else(capacity_overflow)?;
+		let layout = Layout::new::<K, V>(slots)?;
+
+		let mut tmp_path = OsString::from(&self.path);
+		tmp_path.push(".rehash");
+		let tmp_path = PathBuf::from(tmp_path);
+
+		log::debug!(
+			"rehashing MmapHashMap '{}' into {} slots",
+			self.path.display(),
+			slots
+		);
+
+		let grown = unsafe { Self::create_with_layout(&tmp_path, layout) }.and_then(|mut grown| {
+			{
+				let mut table = grown.table_mut();
+				for (key, value) in self.iter() {
+					table.insert(*key, *value)?;
+				}
+			}
+
+			grown.set_len(self.len);
+			grown.sync_all()?;
+
+			fs::rename(&tmp_path, &self.path)?;
+			Ok(grown)
+		});
+
+		let mut grown = match grown {
+			Ok(grown) => grown,
+			Err(e) => {
+				// the file may not have been created at all, so failing to remove it is fine
+				let _ = fs::remove_file(&tmp_path);
+				return Err(e);
+			}
+		};
+
+		grown.path = self.path.clone();
+		*self = grown;
+
+		// makes the rename itself durable
+		#[cfg(unix)]
+		sync_parent_dir(&self.path)?;
+
+		Ok(())
+	}
+
+	fn table(&self) -> Table<'_, K, V> {
+		Table::new(&self.inner.map, &self.layout)
+	}
+
+	fn table_mut(&mut self) -> TableMut<'_, K, V> {
+		TableMut::new(&mut self.inner.map, &self.layout)
+	}
+
+	fn header_mut(&mut self) -> &mut Header {
+		self.inner
+			.view_mut::<Header>(0)
+			.expect("MmapHashMap header is always in bounds and aligned")
+	}
+
+	fn set_len(&mut self, len: usize) {
+		self.len = len;
+		self.header_mut()[5] = len as u64;
+	}
+}
+
+// a read-only view of a `MmapHashMap` file, it shares pages with the writer and is only
+// consistent until the writer's next in-place insert or remove, lookups racing one can miss or
+// return torn entries, views opened before the map grew keep the old file as a snapshot
+pub struct MmapHashMapView<K: Pod, V: Pod> {
+	inner: MmapFile,
+	layout: Layout,
+	len: usize,
+	types: PhantomData<(K, V)>,
+}
+
+impl<K: Pod, V: Pod> MmapHashMapView<K, V> {
+	pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let inner = MmapFile::open(path)?;
+		let (layout, len) = read_header::<K, V>(&inner)?;
+
+		Ok(Self {
+			inner,
+			layout,
+			len,
+			types: PhantomData,
+		})
+	}
+
+	// the length when the view was opened
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn get(&self, key: &K) -> Option<&V> {
+		self.table().get(key)
+	}
+
+	pub fn contains_key(&self, key: &K) -> bool {
+		self.get(key).is_some()
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+		self.table().iter()
+	}
+
+	fn table(&self) -> Table<'_, K, V> {
+		Table::new(&self.inner.map, &self.layout)
+	}
+}
+
+fn new_header<K: Pod, V: Pod>(slots: usize) -> Header {
+	[
+		MAGIC,
+		VERSION,
+		mem::size_of::<K>() as u64,
+		mem::size_of::<V>() as u64,
+		slots as u64,
+		0,
+		0,
+		0,
+	]
+}
+
+fn read_header<K: Pod, V: Pod>(map: &[u8]) -> io::Result<(Layout, usize)> {
+	let header: &[Header] = map
+		.get(..mem::size_of::<Header>())
+		.and_then(|bytes| cast_slice(bytes).ok())
+		.ok_or_else(|| invalid_data("file is too small for a MmapHashMap header"))?;
+	let [magic, version, key_size, value_size, slots, len, _, _] = header[0];
+
+	if magic != MAGIC {
+		return Err(invalid_data("file is not a MmapHashMap"));
+	}
+
+	if version != VERSION {
+		return Err(invalid_data(format!(
+			"unsupported MmapHashMap version {}, expected {}",
+			version, VERSION
+		)));
+	}
+
+	if key_size != mem::size_of::<K>() as u64 || value_size != mem::size_of::<V>() as u64 {
+		return Err(invalid_data(format!(
+			"MmapHashMap entries are {} and {} bytes, expected {} and {}",
+			key_size,
+			value_size,
+			mem::size_of::<K>(),
+			mem::size_of::<V>()
+		)));
+	}
+
+	let slots = usize::try_from(slots)
+		.ok()
+		.filter(|slots| slots.is_power_of_two())
+		.ok_or_else(|| invalid_data("MmapHashMap slot count is not a power of two"))?;
+	let layout = Layout::new::<K, V>(slots)?;
+
+	if map.len() < layout.size || len > layout.capacity() as u64 {
+		return Err(invalid_data("MmapHashMap file is corrupt"));
+	}
+
+	Ok((layout, len as usize))
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+	let parent = match path.parent() {
+		Some(parent) if !parent.as_os_str().is_empty() => parent,
+		_ => Path::new("."),
+	};
+
+	fs::File::open(parent)?.sync_all()
+}
+
+fn regions<K: Pod>(layout: &Layout) -> (Range<usize>, Range<usize>, Range<usize>) {
+	let keys_len = layout.slots * mem::size_of::<K>();
+	(
+		layout.states..layout.states + layout.slots,
+		layout.keys..layout.keys + keys_len,
+		layout.values..layout.size,
+	)
+}
+
+fn key_eq<K: Pod>(a: &K, b: &K) -> bool {
+	bytes_of_slice(slice::from_ref(a)) == bytes_of_slice(slice::from_ref(b))
+}
+
+// FNV-1a followed by a splitmix64 finalizer, it has to stay stable as it is persisted
+fn hash<K: Pod>(key: &K) -> u64 {
+	let mut hash = 0xcbf2_9ce4_8422_2325u64;
+
+	for &byte in bytes_of_slice(slice::from_ref(key)) {
+		hash ^= byte as u64;
+		hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+	}
+
+	hash ^= hash >> 30;
+	hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	hash ^= hash >> 27;
+	hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+	hash ^ (hash >> 31)
+}
+
+fn align_up(n: usize, align: usize) -> io::Result<usize> {
+	checked_add(n, align - 1).map(|n| n / align * align)
+}
+
+fn checked_add(a: usize, b: usize) -> io::Result<usize> {
+	a.checked_add(b).ok_or_else(capacity_overflow)
+}
+
+fn checked_mul(a: usize, b: usize) -> io::Result<usize> {
+	a.checked_mul(b).ok_or_else(capacity_overflow)
+}
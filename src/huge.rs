@@ -0,0 +1,89 @@
+use crate::{advice, memfd::memfd_create, Advice, MmappedFile};
+
+use memmap::MmapMut;
+
+use std::{
+	fs::{self, File},
+	io,
+	mem::MaybeUninit,
+	ops::Deref,
+	os::unix::io::AsRawFd,
+};
+
+// default huge page size reported by the kernel, if it has hugetlb support at all
+pub fn huge_page_size() -> Option<usize> {
+	let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+
+	meminfo
+		.lines()
+		.find(|line| line.starts_with("Hugepagesize:"))
+		.and_then(|line| line.split_whitespace().nth(1))
+		.and_then(|kb| kb.parse::<usize>().ok())
+		.map(|kb| kb * 1024)
+}
+
+// page size of the hugetlbfs `file` lives on, `None` for files on any other filesystem
+pub(crate) fn hugetlb_page_size(file: &File) -> io::Result<Option<u64>> {
+	let mut stat = MaybeUninit::<libc::statfs>::uninit();
+
+	if unsafe { libc::fstatfs(file.as_raw_fd(), stat.as_mut_ptr()) } != 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	let stat = unsafe { stat.assume_init() };
+
+	// f_type's width differs between targets, the magic only fits in 32 bits anyway
+	if stat.f_type as u32 == libc::HUGETLBFS_MAGIC as u32 {
+		Ok(Some(stat.f_bsize as u64))
+	} else {
+		Ok(None)
+	}
+}
+
+impl<M> MmappedFile<M>
+where
+	M: AsRef<[u8]> + Deref<Target = [u8]>,
+{
+	// asks for transparent huge pages via MADV_HUGEPAGE, file maps only get them if the
+	// kernel supports THP for the underlying filesystem
+	pub fn request_huge_pages(&self) -> io::Result<()> {
+		advice::madvise(&self.map, 0..self.map.len(), Advice::HugePage)
+	}
+}
+
+impl MmappedFile<MmapMut> {
+	// an anonymous map backed by explicit huge pages (a MFD_HUGETLB memfd, equivalent to
+	// MAP_HUGETLB), `size` and any later resize are rounded up to the huge page size, falls
+	// back to regular pages with a warning when no huge pages are available
+	pub fn anonymous_huge(size: usize) -> io::Result<Self> {
+		match Self::map_huge(size) {
+			Ok(mmapped) => Ok(mmapped),
+			Err(e) => {
+				log::warn!(
+					"huge pages unavailable, falling back to regular pages '{}'",
+					e
+				);
+
+				Self::anonymous(size)
+			}
+		}
+	}
+
+	fn map_huge(size: usize) -> io::Result<Self> {
+		let huge_page_size =
+			huge_page_size().ok_or_else(|| io::Error::other("kernel has no hugetlb support"))?;
+		let size = size.div_ceil(huge_page_size) * huge_page_size;
+
+		let file = memfd_create("mmap_file", libc::MFD_CLOEXEC | libc::MFD_HUGETLB)?;
+		file.set_len(size as _)?;
+		let map = unsafe { MmapMut::map_mut(&file) }?;
+
+		log::debug!("mapped {} bytes of huge pages", size);
+
+		Ok(Self {
+			file,
+			map,
+			offset: 0,
+		})
+	}
+}
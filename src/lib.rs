@@ -1,10 +1,70 @@
+#![allow(clippy::missing_safety_doc)]
+
+#[cfg(unix)]
+mod advice;
+mod convert;
+mod cow;
+mod durability;
+mod growth;
+mod hashmap;
+#[cfg(target_os = "linux")]
+mod huge;
+#[cfg(unix)]
+mod lock;
+#[cfg(target_os = "linux")]
+mod memfd;
+mod options;
+mod pod;
+#[cfg(unix)]
+mod populate;
+#[cfg(unix)]
+mod residency;
+#[cfg(target_os = "linux")]
+mod seal;
+mod shared;
+mod text;
+mod typed;
+mod vec;
+#[cfg(unix)]
+mod windowed;
+
+#[cfg(unix)]
+pub use advice::Advice;
+#[cfg(feature = "bytemuck")]
+pub use bytemuck;
+pub use cow::{MmapCow, MmapCowFile};
+pub use durability::Durability;
+pub use growth::GrowthPolicy;
+pub use hashmap::{MmapHashMap, MmapHashMapView};
+#[cfg(target_os = "linux")]
+pub use huge::huge_page_size;
+#[cfg(unix)]
+pub use lock::MemoryLock;
+pub use options::{MapMode, MmapOptions};
+pub use pod::{Pod, ViewError};
+#[cfg(unix)]
+pub use populate::Populate;
+#[cfg(unix)]
+pub use residency::Residency;
+#[cfg(target_os = "linux")]
+pub use seal::Seals;
+pub use shared::{MmapSlice, SharedMmap};
+pub use text::LineIndex;
+pub use typed::TypedWriter;
+pub use vec::MmapVec;
+#[cfg(unix)]
+pub use windowed::WindowedMmap;
+
 use memmap::{Mmap, MmapMut};
 
 use std::{
+	convert::TryFrom,
 	fs::{File, OpenOptions},
-	io::{self, Cursor, Write},
+	io::{self, BufRead, Cursor, IoSlice, Read, Seek, SeekFrom, Write},
+	mem::ManuallyDrop,
 	ops::{Deref, DerefMut},
 	path::Path,
+	ptr,
 };
 
 pub type MmapFile = MmappedFile<Mmap>;
@@ -16,6 +76,8 @@ where
 {
 	file: File,
 	map: M,
+	// where in the file the map starts
+	offset: u64,
 }
 
 impl<M> MmappedFile<M>
@@ -29,9 +91,43 @@ where
 	pub fn is_empty(&self) -> io::Result<bool> {
 		Ok(self.len()? == 0)
 	}
+
+	// length of the map, which for maps created with an offset or length is only part of the file
+	pub fn map_len(&self) -> usize {
+		self.map.len()
+	}
+
+	pub fn offset(&self) -> u64 {
+		self.offset
+	}
+
+	// like `FileExt::read_at`, `offset` is relative to the start of the map
+	pub fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+		let start = std::cmp::min(offset, self.map.len() as u64) as usize;
+		let n = std::cmp::min(buf.len(), self.map.len() - start);
+
+		buf[..n].copy_from_slice(&self.map[start..start + n]);
+
+		Ok(n)
+	}
+
+	pub fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+		if self.read_at(buf, offset)? < buf.len() {
+			return Err(io::Error::new(
+				io::ErrorKind::UnexpectedEof,
+				"failed to fill whole buffer",
+			));
+		}
+
+		Ok(())
+	}
 }
 
 impl MmapFile {
+	pub fn options() -> MmapOptions<Mmap> {
+		MmapOptions::new()
+	}
+
 	pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
 		log::info!("Opening memory mapped file '{}'", path.as_ref().display());
 
@@ -40,12 +136,23 @@ impl MmapFile {
 
 		log::debug!("mmapped file open successful");
 
-		Ok(Self { file, map })
+		Ok(Self {
+			file,
+			map,
+			offset: 0,
+		})
 	}
 
 	pub unsafe fn as_str_unchecked(&self) -> &str {
 		std::str::from_utf8_unchecked(self.deref())
 	}
+
+	pub fn into_reader(self) -> MmappedReader {
+		let inner = self;
+		let pos = 0;
+
+		MmappedReader { inner, pos }
+	}
 }
 
 impl MmapMutFile {
@@ -56,14 +163,24 @@ impl MmapMutFile {
 			.read(true)
 			.write(true)
 			.create(true)
-			.open(&path)?;
+			.truncate(false)
+			.open(path)?;
 
 		file.set_len(size as _)?;
 		let map = MmapMut::map_mut(&file)?;
 
 		log::debug!("mmapped file creation successful, size '{}'", size);
 
-		Ok(Self { file, map })
+		Ok(Self {
+			file,
+			map,
+			offset: 0,
+		})
+	}
+
+	// maps an existing file read-write at its current length, without truncating it
+	pub unsafe fn open_rw<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		MmapFile::options().read_write().open(path)
 	}
 
 	pub unsafe fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
@@ -72,23 +189,207 @@ impl MmapMutFile {
 		Self::create_with_size(path.as_ref(), DEFAULT_SZ)
 	}
 
-	fn resize(&mut self, new_len: u64) -> io::Result<()> {
-		self.file.set_len(new_len)?;
-		self.map = unsafe { MmapMut::map_mut(&self.file) }?;
+	// resizes the file to hold `new_len` bytes past the start of the map and remaps it,
+	// taking `&mut self` guarantees no borrows of the old map outlive it
+	pub fn resize(&mut self, new_len: u64) -> io::Result<()> {
+		// checked up front, as failing to remap after `set_len` would leave a stale map
+		if new_len == 0 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"cannot resize a memory map to zero length",
+			));
+		}
+
+		// hugetlbfs only accepts lengths in whole huge pages
+		#[cfg(target_os = "linux")]
+		let new_len = match huge::hugetlb_page_size(&self.file)? {
+			Some(page_size) => new_len
+				.checked_add(page_size - 1)
+				.ok_or_else(overflow_error)?
+				/ page_size * page_size,
+			None => new_len,
+		};
+
+		let new_len_usize = usize::try_from(new_len).map_err(|_| overflow_error())?;
+		let new_end = self.offset.checked_add(new_len).ok_or_else(overflow_error)?;
+
+		// a map running to the end of the file resizes the file with it, a map over a sub-range
+		// only grows the file when it has to, so the bytes after the map are never truncated
+		if new_end > self.len()? || self.reaches_eof()? {
+			if let Err(e) = self.file.set_len(new_end) {
+				#[cfg(target_os = "linux")]
+				let e = seal::resize_error(&self.file, e);
+
+				return Err(e);
+			}
+		}
+
+		self.map = unsafe {
+			memmap::MmapOptions::new()
+				.offset(self.offset)
+				.len(new_len_usize)
+				.map_mut(&self.file)
+		}?;
+		Ok(())
+	}
+
+	fn reaches_eof(&self) -> io::Result<bool> {
+		Ok(self.offset + self.map.len() as u64 >= self.len()?)
+	}
+
+	// like `FileExt::write_at`, but grows the file when the write runs past the end of the map
+	pub fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+		// nothing to write, so never grow the map for it
+		if buf.is_empty() {
+			return Ok(0);
+		}
+
+		let end = offset
+			.checked_add(buf.len() as u64)
+			.ok_or_else(overflow_error)?;
+
+		if end > self.map.len() as u64 {
+			self.resize(end)?;
+		}
+
+		self.map[offset as usize..end as usize].copy_from_slice(buf);
+
+		Ok(buf.len())
+	}
+
+	pub fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+		let _ = self.write_at(buf, offset)?;
 		Ok(())
 	}
 
+	pub fn flush(&self) -> io::Result<()> {
+		self.map.flush()
+	}
+
+	pub fn flush_async(&self) -> io::Result<()> {
+		// memmap aligns the range by the map offset rather than its address, which fails for
+		// maps at offsets that aren't page aligned
+		#[cfg(unix)]
+		{
+			let (ptr, len) = page_aligned(&self.map, 0..self.map.len())?;
+
+			if unsafe { libc::msync(ptr, len, libc::MS_ASYNC) } != 0 {
+				return Err(io::Error::last_os_error());
+			}
+
+			Ok(())
+		}
+
+		#[cfg(not(unix))]
+		self.map.flush_async()
+	}
+
+	pub fn sync_data(&self) -> io::Result<()> {
+		self.map.flush()?;
+		self.file.sync_data()
+	}
+
+	pub fn sync_all(&self) -> io::Result<()> {
+		self.map.flush()?;
+		self.file.sync_all()
+	}
+
+	// NOTE: the writer treats the map as empty, finishing or dropping it truncates the file to
+	// what was written through it, use `into_append_writer` to keep the existing contents
 	pub fn into_writer(self) -> MmappedWriter {
+		self.into_writer_at(0)
+	}
+
+	// a writer positioned at the end of the map, which keeps its current contents, for
+	// appending to files opened with `open_rw`
+	pub fn into_append_writer(self) -> MmappedWriter {
+		let len = self.map.len();
+		self.into_writer_at(len)
+	}
+
+	fn into_writer_at(self, len: usize) -> MmappedWriter {
 		let inner = self;
-		let pos = 0;
+		let pos = len;
+		let growth = GrowthPolicy::default();
+		let durability = Durability::default();
+
+		MmappedWriter {
+			inner,
+			pos,
+			len,
+			growth,
+			durability,
+		}
+	}
+}
+
+pub struct MmappedReader {
+	inner: MmappedFile<Mmap>,
+	pos: usize,
+}
+
+impl MmappedReader {
+	pub fn position(&self) -> usize {
+		self.pos
+	}
+
+	pub fn remaining(&self) -> &[u8] {
+		// pos may be seeked past the end of the map
+		let start = std::cmp::min(self.pos, self.inner.map.len());
+		&self.inner.map[start..]
+	}
+
+	pub fn get_ref(&self) -> &MmappedFile<Mmap> {
+		&self.inner
+	}
+
+	pub fn into_inner(self) -> MmappedFile<Mmap> {
+		self.inner
+	}
+}
+
+impl Read for MmappedReader {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let n = self.remaining().read(buf)?;
+		self.pos += n;
+
+		Ok(n)
+	}
+
+	fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+		self.remaining().read_exact(buf)?;
+		self.pos += buf.len();
+
+		Ok(())
+	}
+}
+
+impl BufRead for MmappedReader {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		// the whole map is already in memory, hand it out without copying
+		Ok(self.remaining())
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.pos += amt;
+	}
+}
+
+impl Seek for MmappedReader {
+	fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+		self.pos = seek_position(pos, self.inner.map.len(), self.pos)?;
 
-		MmappedWriter { inner, pos }
+		Ok(self.pos as u64)
 	}
 }
 
 pub struct MmappedWriter {
 	inner: MmappedFile<MmapMut>,
 	pos: usize,
+	// high-water mark, the end of the furthest byte written so far
+	len: usize,
+	growth: GrowthPolicy,
+	durability: Durability,
 }
 
 impl MmappedWriter {
@@ -104,6 +405,115 @@ impl MmappedWriter {
 		Ok(())
 	}
 
+	// logical length of the written data, which is what the file gets truncated to on drop
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn position(&self) -> usize {
+		self.pos
+	}
+
+	pub fn growth_policy(&self) -> GrowthPolicy {
+		self.growth
+	}
+
+	pub fn set_growth_policy(&mut self, growth: GrowthPolicy) {
+		self.growth = growth;
+	}
+
+	pub fn with_growth_policy(mut self, growth: GrowthPolicy) -> Self {
+		self.growth = growth;
+		self
+	}
+
+	pub fn durability(&self) -> Durability {
+		self.durability
+	}
+
+	pub fn set_durability(&mut self, durability: Durability) {
+		self.durability = durability;
+	}
+
+	pub fn with_durability(mut self, durability: Durability) -> Self {
+		self.durability = durability;
+		self
+	}
+
+	pub fn sync_data(&self) -> io::Result<()> {
+		self.inner.sync_data()
+	}
+
+	pub fn sync_all(&self) -> io::Result<()> {
+		self.inner.sync_all()
+	}
+
+	// truncates the file to the written length and flushes it according to the durability
+	// setting, reporting any errors instead of just logging them like `Drop` does
+	pub fn finish(self) -> io::Result<MmappedFile<MmapMut>> {
+		let mut this = ManuallyDrop::new(self);
+		let res = this.truncate_and_flush();
+
+		// `this` is never used or dropped again, so `inner` is only moved out once
+		let inner = unsafe { ptr::read(&this.inner) };
+
+		res?;
+
+		if this.len == 0 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"nothing was written, cannot map an empty file",
+			));
+		}
+
+		Ok(inner)
+	}
+
+	pub fn finish_read_only(self) -> io::Result<MmappedFile<Mmap>> {
+		self.finish()?.make_read_only()
+	}
+
+	fn truncate_and_flush(&mut self) -> io::Result<()> {
+		log::trace!("truncating mmapped file to '{}' bytes", self.len);
+
+		if self.len == 0 {
+			// an empty map can't exist, so only the file is truncated and the old map left as is,
+			// the tail of a map over a sub-range is kept
+			if self.inner.reaches_eof()? {
+				self.inner.file.set_len(self.inner.offset)?;
+			}
+
+			return match self.durability {
+				Durability::SyncAll => self.inner.file.sync_all(),
+				_ => Ok(()),
+			};
+		}
+
+		self.inner.resize(self.len as _)?;
+
+		// flush after the truncation, so with `Durability::SyncAll` the new length is durable too
+		self.flush()
+	}
+
+	fn end_of_write(&self, len: usize) -> io::Result<usize> {
+		self.pos.checked_add(len).ok_or_else(overflow_error)
+	}
+
+	// grows the map once, up front, so that `end` bytes are guaranteed to fit
+	fn grow_to_fit(&mut self, end: usize) -> io::Result<()> {
+		let map_len = self.inner.map.len();
+
+		if end > map_len {
+			self.resize(self.growth.next_len(map_len, end))?;
+		}
+
+		Ok(())
+	}
+
 	fn generate_cursor(&mut self) -> Cursor<&mut [u8]> {
 		let mut cursor = Cursor::new(&mut *self.inner.map);
 		cursor.set_position(self.pos as _);
@@ -113,45 +523,150 @@ impl MmappedWriter {
 
 impl Write for MmappedWriter {
 	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let end = self.end_of_write(buf.len())?;
 
-		while let Err(e) = self.generate_cursor().write_all(&buf) {
-			if e.kind() == io::ErrorKind::WriteZero {
-				self.resize(2 * self.inner.map.len())?;
-			} else {
-				return Err(e);
-			}
-		}
+		self.grow_to_fit(end)?;
+		self.generate_cursor().write_all(buf)?;
 
 		self.pos += buf.len();
+		self.len = std::cmp::max(self.len, self.pos);
 
 		Ok(buf.len())
 	}
 
+	// `is_write_vectored` can't be overridden on stable, but this is always efficient
+	fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+		let total = bufs
+			.iter()
+			.try_fold(0usize, |total, buf| total.checked_add(buf.len()))
+			.ok_or_else(overflow_error)?;
+		let end = self.end_of_write(total)?;
+
+		// a single resize for the whole batch
+		self.grow_to_fit(end)?;
+
+		let mut cursor = self.generate_cursor();
+		for buf in bufs {
+			cursor.write_all(buf)?;
+		}
+
+		self.pos = end;
+		self.len = std::cmp::max(self.len, self.pos);
+
+		Ok(total)
+	}
+
 	fn flush(&mut self) -> io::Result<()> {
-		self.inner.map.flush_async()
+		match self.durability {
+			Durability::None => Ok(()),
+			Durability::Async => self.inner.flush_async(),
+			Durability::Sync => self.inner.flush(),
+			Durability::SyncAll => self.inner.sync_all(),
+		}
 	}
 
 	fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
 		// write already forces a write_all
-		let _ = self.write(&buf)?;
+		let _ = self.write(buf)?;
 		Ok(())
 	}
 }
 
+impl Seek for MmappedWriter {
+	fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+		let new_pos = seek_position(pos, self.len, self.pos)?;
+
+		// seeking past the end of the map grows the file, but doesn't move the
+		// high-water mark until something is actually written there
+		if new_pos > self.inner.map.len() {
+			self.resize(new_pos)?;
+		}
+
+		self.pos = new_pos;
+
+		Ok(new_pos as u64)
+	}
+}
+
 impl Drop for MmappedWriter {
 	fn drop(&mut self) {
-		if let Err(e) = self.inner.resize(self.pos as _) {
+		// fallback for writers that weren't `finish`ed, errors can only be logged here
+		if let Err(e) = self.truncate_and_flush() {
 			log::error!("error when dropping MmappedWriter '{}'", e)
-		} else {
-			log::trace!(
-				"dropping mmapped file writer, truncating to '{}' bytes",
-				self.pos
-			);
 		}
+	}
+}
 
-		if let Err(e) = self.flush() {
-			log::error!("{}", e);
-		}
+#[cfg(unix)]
+fn page_size() -> usize {
+	unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+// widens `range` of `map` to whole pages, for the syscalls that require page aligned addresses
+#[cfg(unix)]
+fn page_aligned(map: &[u8], range: std::ops::Range<usize>) -> io::Result<(*mut libc::c_void, usize)> {
+	if range.start > range.end || range.end > map.len() {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!(
+				"range {}..{} is out of bounds for map of {} bytes",
+				range.start,
+				range.end,
+				map.len()
+			),
+		));
+	}
+
+	let start = map.as_ptr() as usize + range.start;
+	let aligned_start = start - start % page_size();
+	let len = range.end - range.start + (start - aligned_start);
+
+	Ok((aligned_start as *mut libc::c_void, len))
+}
+
+fn overflow_error() -> io::Error {
+	io::Error::new(
+		io::ErrorKind::InvalidInput,
+		"write past the end of addressable memory",
+	)
+}
+
+// the position `pos` resolves to, relative to `end` and `current`
+fn seek_position(pos: SeekFrom, end: usize, current: usize) -> io::Result<usize> {
+	let new_pos = match pos {
+		SeekFrom::Start(n) => Some(n),
+		SeekFrom::End(n) => (end as u64).checked_add_signed(n),
+		SeekFrom::Current(n) => (current as u64).checked_add_signed(n),
+	};
+
+	new_pos
+		.and_then(|p| usize::try_from(p).ok())
+		.ok_or_else(|| {
+			io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"invalid seek to a negative or overflowing position",
+			)
+		})
+}
+
+fn capacity_overflow() -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidInput, "capacity overflow")
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+	E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+	io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(unix)]
+impl<M> std::os::unix::io::AsRawFd for MmappedFile<M>
+where
+	M: AsRef<[u8]> + Deref<Target = [u8]>,
+{
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.file.as_raw_fd()
 	}
 }
 
@@ -0,0 +1,107 @@
+use crate::{page_aligned, MmappedFile};
+
+use std::{io, marker::PhantomData, ops::Deref, ops::Range};
+
+// pages locked in memory with `mlock`, unlocked again on drop
+pub struct MemoryLock<'a> {
+	ptr: *mut libc::c_void,
+	len: usize,
+	map: PhantomData<&'a [u8]>,
+}
+
+impl MemoryLock<'_> {
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	// unlocks the pages, reporting any error instead of just logging it like `Drop` does
+	pub fn unlock(self) -> io::Result<()> {
+		let res = munlock(self.ptr, self.len);
+		std::mem::forget(self);
+		res
+	}
+}
+
+impl Drop for MemoryLock<'_> {
+	fn drop(&mut self) {
+		if let Err(e) = munlock(self.ptr, self.len) {
+			log::error!("error when dropping MemoryLock '{}'", e);
+		}
+	}
+}
+
+unsafe impl Send for MemoryLock<'_> {}
+unsafe impl Sync for MemoryLock<'_> {}
+
+impl<M> MmappedFile<M>
+where
+	M: AsRef<[u8]> + Deref<Target = [u8]>,
+{
+	pub fn lock(&self) -> io::Result<MemoryLock<'_>> {
+		self.lock_range(0..self.map.len())
+	}
+
+	// `range` is widened to the enclosing pages, as mlock only works on whole pages
+	pub fn lock_range(&self, range: Range<usize>) -> io::Result<MemoryLock<'_>> {
+		let (ptr, len) = page_aligned(&self.map, range)?;
+
+		log::trace!("mlock on {} bytes", len);
+
+		if unsafe { libc::mlock(ptr, len) } != 0 {
+			return Err(mlock_error(len));
+		}
+
+		Ok(MemoryLock {
+			ptr,
+			len,
+			map: PhantomData,
+		})
+	}
+
+	// unlocks the whole map, regardless of any outstanding `MemoryLock`s
+	pub fn unlock(&self) -> io::Result<()> {
+		let (ptr, len) = page_aligned(&self.map, 0..self.map.len())?;
+		munlock(ptr, len)
+	}
+}
+
+fn munlock(ptr: *mut libc::c_void, len: usize) -> io::Result<()> {
+	log::trace!("munlock on {} bytes", len);
+
+	if unsafe { libc::munlock(ptr, len) } != 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	Ok(())
+}
+
+// ENOMEM and EAGAIN usually mean RLIMIT_MEMLOCK was hit, which deserves a clearer message
+fn mlock_error(len: usize) -> io::Error {
+	let e = io::Error::last_os_error();
+
+	match e.raw_os_error() {
+		Some(libc::ENOMEM) | Some(libc::EAGAIN) => {
+			let mut limit = libc::rlimit {
+				rlim_cur: 0,
+				rlim_max: 0,
+			};
+
+			if unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) } != 0 {
+				return e;
+			}
+
+			io::Error::new(
+				e.kind(),
+				format!(
+					"cannot lock {} bytes, RLIMIT_MEMLOCK is {} bytes ({})",
+					len, limit.rlim_cur, e
+				),
+			)
+		}
+		_ => e,
+	}
+}
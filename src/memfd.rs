@@ -0,0 +1,49 @@
+use crate::MmappedFile;
+
+use memmap::MmapMut;
+
+use std::{ffi::CString, fs::File, io, os::unix::io::FromRawFd};
+
+impl MmappedFile<MmapMut> {
+	// a scratch map that isn't backed by any file on disk
+	pub fn anonymous(size: usize) -> io::Result<Self> {
+		Self::memfd("mmap_file", size)
+	}
+
+	// a map backed by a memfd, which can be shared with other processes by passing its fd,
+	// `name` is only used for debugging and shows up in /proc/self/fd
+	pub fn memfd(name: &str, size: usize) -> io::Result<Self> {
+		log::info!("Creating memfd backed memory mapped file '{}'", name);
+
+		let file = memfd_create(name, libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING)?;
+		file.set_len(size as _)?;
+
+		// nothing else can have the memfd yet, so it can't be modified from under the map
+		let map = unsafe { MmapMut::map_mut(&file) }?;
+
+		log::debug!("memfd creation successful, size '{}'", size);
+
+		Ok(Self {
+			file,
+			map,
+			offset: 0,
+		})
+	}
+}
+
+pub(crate) fn memfd_create(name: &str, flags: libc::c_uint) -> io::Result<File> {
+	let name = CString::new(name).map_err(|_| {
+		io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"memfd name contains a nul byte",
+		)
+	})?;
+
+	let fd = unsafe { libc::memfd_create(name.as_ptr(), flags) };
+
+	if fd < 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	Ok(unsafe { File::from_raw_fd(fd) })
+}
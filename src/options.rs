@@ -0,0 +1,278 @@
+use crate::MmappedFile;
+#[cfg(unix)]
+use crate::{advice, Advice, Populate};
+
+use memmap::{Mmap, MmapMut};
+
+use std::{
+	fs::{File, OpenOptions},
+	io,
+	marker::PhantomData,
+	ops::Deref,
+	path::Path,
+};
+
+// the kinds of map a `MmapOptions` can produce, sealed so it can only be implemented in this
+// crate and memmap's types stay out of the public API
+pub trait MapMode: sealed::Sealed + AsRef<[u8]> + Deref<Target = [u8]> + Sized {}
+
+pub(crate) mod sealed {
+	use std::{fs::File, io};
+
+	pub trait Sealed: Sized {
+		const WRITABLE: bool;
+		// private maps hold pages of their own, which are lost when they are dropped
+		const PRIVATE: bool;
+
+		unsafe fn map(options: &memmap::MmapOptions, file: &File) -> io::Result<Self>;
+	}
+}
+
+impl MapMode for Mmap {}
+
+impl sealed::Sealed for Mmap {
+	const WRITABLE: bool = false;
+	const PRIVATE: bool = false;
+
+	unsafe fn map(options: &memmap::MmapOptions, file: &File) -> io::Result<Self> {
+		options.map(file)
+	}
+}
+
+impl MapMode for MmapMut {}
+
+impl sealed::Sealed for MmapMut {
+	const WRITABLE: bool = true;
+	const PRIVATE: bool = false;
+
+	unsafe fn map(options: &memmap::MmapOptions, file: &File) -> io::Result<Self> {
+		options.map_mut(file)
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct MmapOptions<M> {
+	offset: u64,
+	len: Option<usize>,
+	size: Option<u64>,
+	create: bool,
+	create_new: bool,
+	truncate: bool,
+	#[cfg(unix)]
+	advice: Option<Advice>,
+	#[cfg(unix)]
+	populate: Option<Populate>,
+	#[cfg(target_os = "linux")]
+	huge_pages: bool,
+	mode: PhantomData<M>,
+}
+
+impl MmapOptions<Mmap> {
+	pub fn new() -> Self {
+		Self {
+			offset: 0,
+			len: None,
+			size: None,
+			create: false,
+			create_new: false,
+			truncate: false,
+			#[cfg(unix)]
+			advice: None,
+			#[cfg(unix)]
+			populate: None,
+			#[cfg(target_os = "linux")]
+			huge_pages: false,
+			mode: PhantomData,
+		}
+	}
+
+	pub fn read_write(self) -> MmapOptions<MmapMut> {
+		self.with_mode()
+	}
+}
+
+impl Default for MmapOptions<Mmap> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MmapOptions<MmapMut> {
+	pub fn read_only(self) -> MmapOptions<Mmap> {
+		self.with_mode()
+	}
+}
+
+impl<M> MmapOptions<M>
+where
+	M: MapMode,
+{
+	// byte offset into the file at which the map starts, it need not be page aligned
+	pub fn offset(mut self, offset: u64) -> Self {
+		self.offset = offset;
+		self
+	}
+
+	// length of the map, defaults to the rest of the file after `offset`
+	pub fn len(mut self, len: usize) -> Self {
+		self.len = Some(len);
+		self
+	}
+
+	// resize the file to `size` bytes before mapping it, requires `read_write`
+	pub fn size(mut self, size: u64) -> Self {
+		self.size = Some(size);
+		self
+	}
+
+	pub fn create(mut self, create: bool) -> Self {
+		self.create = create;
+		self
+	}
+
+	pub fn create_new(mut self, create_new: bool) -> Self {
+		self.create_new = create_new;
+		self
+	}
+
+	pub fn truncate(mut self, truncate: bool) -> Self {
+		self.truncate = truncate;
+		self
+	}
+
+	// access pattern hint applied to the map as soon as it is created
+	#[cfg(unix)]
+	pub fn advise(mut self, advice: Advice) -> Self {
+		self.advice = Some(advice);
+		self
+	}
+
+	#[cfg(unix)]
+	pub fn populate(mut self, populate: Populate) -> Self {
+		self.populate = Some(populate);
+		self
+	}
+
+	// requests transparent huge pages for the map, falling back to regular pages with a
+	// warning when they are unavailable
+	#[cfg(target_os = "linux")]
+	pub fn huge_pages(mut self, huge_pages: bool) -> Self {
+		self.huge_pages = huge_pages;
+		self
+	}
+
+	pub unsafe fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<MmappedFile<M>> {
+		let path = path.as_ref();
+
+		log::info!("Opening memory mapped file '{}'", path.display());
+
+		if self.size.is_some() && !M::WRITABLE {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"setting the file size requires a read-write map",
+			));
+		}
+
+		// OpenOptions itself rejects create and truncate without write access
+		let file = OpenOptions::new()
+			.read(true)
+			.write(M::WRITABLE)
+			.create(self.create)
+			.create_new(self.create_new)
+			.truncate(self.truncate)
+			.open(path)?;
+
+		if let Some(size) = self.size {
+			file.set_len(size)?;
+		}
+
+		let file_len = file.metadata()?.len();
+
+		// mmap rejects zero-length maps with a rather unhelpful error
+		if file_len == 0 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				format!("cannot memory map empty file '{}'", path.display()),
+			));
+		}
+
+		let len = self.len.unwrap_or(0) as u64;
+
+		match self.offset.checked_add(len) {
+			Some(map_end) if map_end <= file_len => (),
+			_ => {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					format!(
+						"map range of {} bytes at offset {} is out of bounds for file of {} bytes",
+						len, self.offset, file_len
+					),
+				))
+			}
+		}
+
+		let mut options = memmap::MmapOptions::new();
+		options.offset(self.offset);
+
+		if let Some(len) = self.len {
+			options.len(len);
+		}
+
+		let map = M::map(&options, &file)?;
+
+		#[cfg(unix)]
+		if let Some(advice) = self.advice {
+			advice::madvise(&map, 0..map.len(), advice)?;
+		}
+
+		log::debug!(
+			"mmapped file open successful, offset '{}', size '{}'",
+			self.offset,
+			map.len()
+		);
+
+		let mmapped = MmappedFile {
+			file,
+			map,
+			offset: self.offset,
+		};
+
+		#[cfg(target_os = "linux")]
+		if self.huge_pages {
+			if let Err(e) = mmapped.request_huge_pages() {
+				log::warn!(
+					"transparent huge pages unavailable, falling back to regular pages '{}'",
+					e
+				);
+			}
+		}
+
+		// populate last, so the pages are faulted in as huge pages if possible
+		#[cfg(unix)]
+		match self.populate {
+			Some(Populate::Blocking) => mmapped.populate()?,
+			Some(Populate::Background) => drop(mmapped.populate_in_background()?),
+			None => (),
+		}
+
+		Ok(mmapped)
+	}
+
+	pub(crate) fn with_mode<N>(self) -> MmapOptions<N> {
+		MmapOptions {
+			offset: self.offset,
+			len: self.len,
+			size: self.size,
+			create: self.create,
+			create_new: self.create_new,
+			truncate: self.truncate,
+			#[cfg(unix)]
+			advice: self.advice,
+			#[cfg(unix)]
+			populate: self.populate,
+			#[cfg(target_os = "linux")]
+			huge_pages: self.huge_pages,
+			mode: PhantomData,
+		}
+	}
+}
@@ -0,0 +1,184 @@
+use crate::MmappedFile;
+
+use std::{
+	error, fmt, io,
+	mem,
+	ops::{Deref, DerefMut},
+	slice,
+};
+
+// types that can be viewed directly from mapped bytes: `Copy`, without padding, and valid for
+// any bit pattern, e.g. integers, floats and `#[repr(C)]` structs made of them
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+	($($t:ty),*) => {
+		$(unsafe impl Pod for $t {})*
+	};
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+// implements `Pod` for types that implement `bytemuck::Pod`, e.g. through its derive, the
+// bound is checked at compile time so it needs no unsafe at the call site
+#[cfg(feature = "bytemuck")]
+#[macro_export]
+macro_rules! impl_pod_via_bytemuck {
+	($($t:ty),* $(,)?) => {
+		$(unsafe impl $crate::Pod for $t where $t: $crate::bytemuck::Pod {})*
+	};
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewError {
+	// the bytes don't start at a multiple of the type's alignment
+	Misaligned { addr: usize, align: usize },
+	// the bytes don't divide evenly into values of `size` bytes
+	Length { len: usize, size: usize },
+	// fewer bytes are left after the offset than the values `view` asked for need
+	TooShort { len: usize, required: usize },
+	ZeroSized,
+}
+
+impl fmt::Display for ViewError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			ViewError::Misaligned { addr, align } => write!(
+				f,
+				"address {:#x} is not aligned to {} bytes",
+				addr, align
+			),
+			ViewError::Length { len, size } => write!(
+				f,
+				"{} bytes can't be viewed as values of {} bytes",
+				len, size
+			),
+			ViewError::TooShort { len, required } => write!(
+				f,
+				"{} bytes are needed but only {} are left",
+				required, len
+			),
+			ViewError::ZeroSized => write!(f, "can't view bytes as a zero-sized type"),
+		}
+	}
+}
+
+impl error::Error for ViewError {}
+
+impl From<ViewError> for io::Error {
+	fn from(e: ViewError) -> Self {
+		io::Error::new(io::ErrorKind::InvalidData, e)
+	}
+}
+
+impl<M> MmappedFile<M>
+where
+	M: AsRef<[u8]> + Deref<Target = [u8]>,
+{
+	// the whole map as a slice of `T`, its length must be a multiple of `T`'s size
+	pub fn as_slice_of<T: Pod>(&self) -> Result<&[T], ViewError> {
+		cast_slice(&self.map)
+	}
+
+	pub fn view<T: Pod>(&self, offset: usize) -> Result<&T, ViewError> {
+		let bytes = self.map.get(offset..).unwrap_or(&[]);
+		let bytes = bytes.get(..mem::size_of::<T>()).ok_or(ViewError::TooShort {
+			len: bytes.len(),
+			required: mem::size_of::<T>(),
+		})?;
+
+		cast_slice(bytes).map(|values| &values[0])
+	}
+
+	// `count` values of `T` starting at byte `offset`
+	pub fn view_slice<T: Pod>(&self, offset: usize, count: usize) -> Result<&[T], ViewError> {
+		let bytes = self.map.get(offset..).unwrap_or(&[]);
+		let size = count.saturating_mul(mem::size_of::<T>());
+		let bytes = bytes.get(..size).ok_or(ViewError::TooShort {
+			len: bytes.len(),
+			required: size,
+		})?;
+
+		cast_slice(bytes)
+	}
+}
+
+impl<M> MmappedFile<M>
+where
+	M: AsRef<[u8]> + DerefMut<Target = [u8]>,
+{
+	pub fn as_mut_slice_of<T: Pod>(&mut self) -> Result<&mut [T], ViewError> {
+		cast_slice_mut(&mut self.map)
+	}
+
+	pub fn view_mut<T: Pod>(&mut self, offset: usize) -> Result<&mut T, ViewError> {
+		let bytes = self.map.get_mut(offset..).unwrap_or(&mut []);
+		let len = bytes.len();
+		let bytes = bytes
+			.get_mut(..mem::size_of::<T>())
+			.ok_or(ViewError::TooShort {
+				len,
+				required: mem::size_of::<T>(),
+			})?;
+
+		cast_slice_mut(bytes).map(|values| &mut values[0])
+	}
+
+	pub fn view_slice_mut<T: Pod>(
+		&mut self,
+		offset: usize,
+		count: usize,
+	) -> Result<&mut [T], ViewError> {
+		let bytes = self.map.get_mut(offset..).unwrap_or(&mut []);
+		let len = bytes.len();
+		let size = count.saturating_mul(mem::size_of::<T>());
+		let bytes = bytes.get_mut(..size).ok_or(ViewError::TooShort {
+			len,
+			required: size,
+		})?;
+
+		cast_slice_mut(bytes)
+	}
+}
+
+fn check<T: Pod>(bytes: &[u8]) -> Result<usize, ViewError> {
+	let size = mem::size_of::<T>();
+	let align = mem::align_of::<T>();
+
+	if size == 0 {
+		return Err(ViewError::ZeroSized);
+	}
+
+	// the address, as maps are only page aligned if the file offset was
+	let addr = bytes.as_ptr() as usize;
+	if !addr.is_multiple_of(align) {
+		return Err(ViewError::Misaligned { addr, align });
+	}
+
+	if !bytes.len().is_multiple_of(size) {
+		return Err(ViewError::Length {
+			len: bytes.len(),
+			size,
+		});
+	}
+
+	Ok(bytes.len() / size)
+}
+
+pub(crate) fn cast_slice<T: Pod>(bytes: &[u8]) -> Result<&[T], ViewError> {
+	let len = check::<T>(bytes)?;
+
+	Ok(unsafe { slice::from_raw_parts(bytes.as_ptr() as *const T, len) })
+}
+
+pub(crate) fn cast_slice_mut<T: Pod>(bytes: &mut [u8]) -> Result<&mut [T], ViewError> {
+	let len = check::<T>(bytes)?;
+
+	Ok(unsafe { slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut T, len) })
+}
+
+pub(crate) fn bytes_of_slice<T: Pod>(values: &[T]) -> &[u8] {
+	unsafe { slice::from_raw_parts(values.as_ptr() as *const u8, mem::size_of_val(values)) }
+}
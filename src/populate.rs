@@ -0,0 +1,85 @@
+#[cfg(target_os = "linux")]
+use crate::{advice, Advice};
+use crate::{page_size, MmappedFile};
+
+use std::{
+	io,
+	ops::Deref,
+	ptr,
+	thread::{self, JoinHandle},
+	time::Instant,
+};
+
+// how `MmapOptions::populate` prefaults a map
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Populate {
+	// prefault the map before `open` returns
+	Blocking,
+	// read the file into the page cache from a background thread, `open` returns immediately
+	Background,
+}
+
+impl<M> MmappedFile<M>
+where
+	M: AsRef<[u8]> + Deref<Target = [u8]>,
+{
+	// prefaults every page of the map for reading, so the first pass over it doesn't take
+	// page faults in the hot path
+	pub fn populate(&self) -> io::Result<()> {
+		let start = Instant::now();
+
+		populate(&self.map);
+
+		log::debug!(
+			"prefaulted {} bytes in {:?}",
+			self.map.len(),
+			start.elapsed()
+		);
+
+		Ok(())
+	}
+
+	// prefaults a separate map of the same range on a background thread, that way the file is
+	// pulled into the page cache without the thread borrowing this map
+	pub fn populate_in_background(&self) -> io::Result<JoinHandle<()>> {
+		let file = self.file.try_clone()?;
+		let map = unsafe {
+			memmap::MmapOptions::new()
+				.offset(self.offset)
+				.len(self.map.len())
+				.map(&file)
+		}?;
+
+		thread::Builder::new()
+			.name("mmap-populate".into())
+			.spawn(move || {
+				let start = Instant::now();
+
+				populate(&map);
+
+				log::debug!(
+					"prefaulted {} bytes in the background in {:?}",
+					map.len(),
+					start.elapsed()
+				);
+			})
+	}
+}
+
+// memmap can't pass MAP_POPULATE, MADV_POPULATE_READ is the next best thing on Linux, writable
+// maps are read populated too, like MAP_POPULATE does for shared maps, as MADV_POPULATE_WRITE
+// would dirty every page and allocate blocks for every hole in the file
+pub(crate) fn populate(map: &[u8]) {
+	#[cfg(target_os = "linux")]
+	match advice::madvise(map, 0..map.len(), Advice::PopulateRead) {
+		Ok(()) => return,
+		Err(e) => log::debug!(
+			"MADV_POPULATE_READ failed, touching every page instead '{}'",
+			e
+		),
+	}
+
+	for i in (0..map.len()).step_by(page_size()) {
+		unsafe { ptr::read_volatile(&map[i]) };
+	}
+}
@@ -0,0 +1,84 @@
+use crate::{page_aligned, page_size, MmappedFile};
+
+use std::{cmp, io, ops::Deref};
+
+// which pages of a map are resident in memory, as reported by `mincore`
+#[derive(Clone, Debug)]
+pub struct Residency {
+	pages: Vec<bool>,
+	page_size: usize,
+	// bytes of the first page before the start of the map
+	lead: usize,
+	len: usize,
+}
+
+impl Residency {
+	pub fn page_size(&self) -> usize {
+		self.page_size
+	}
+
+	pub fn total_pages(&self) -> usize {
+		self.pages.len()
+	}
+
+	pub fn resident_pages(&self) -> usize {
+		self.pages.iter().filter(|&&resident| resident).count()
+	}
+
+	// only counts the bytes of resident pages that are part of the map
+	pub fn resident_bytes(&self) -> usize {
+		self.iter()
+			.enumerate()
+			.filter(|&(_, resident)| resident)
+			.map(|(page, _)| {
+				let start = cmp::max(page * self.page_size, self.lead);
+				let end = cmp::min((page + 1) * self.page_size, self.lead + self.len);
+				end - start
+			})
+			.sum()
+	}
+
+	// fraction of the mapped bytes that are resident, between 0 and 1
+	pub fn ratio(&self) -> f64 {
+		if self.len == 0 {
+			return 1.0;
+		}
+
+		self.resident_bytes() as f64 / self.len as f64
+	}
+
+	pub fn is_resident(&self, page: usize) -> bool {
+		self.pages.get(page).copied().unwrap_or(false)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+		self.pages.iter().copied()
+	}
+}
+
+impl<M> MmappedFile<M>
+where
+	M: AsRef<[u8]> + Deref<Target = [u8]>,
+{
+	pub fn residency(&self) -> io::Result<Residency> {
+		let page_size = page_size();
+		let (ptr, len) = page_aligned(&self.map, 0..self.map.len())?;
+		let lead = len - self.map.len();
+
+		let mut vec = vec![0u8; len.div_ceil(page_size)];
+
+		if !vec.is_empty() && unsafe { libc::mincore(ptr, len, vec.as_mut_ptr() as _) } != 0 {
+			return Err(io::Error::last_os_error());
+		}
+
+		// only the lowest bit is defined, the rest are reserved
+		let pages = vec.into_iter().map(|page| page & 1 == 1).collect();
+
+		Ok(Residency {
+			pages,
+			page_size,
+			lead,
+			len: self.map.len(),
+		})
+	}
+}
@@ -0,0 +1,142 @@
+use crate::MmappedFile;
+
+use memmap::{Mmap, MmapMut};
+
+use std::{
+	fmt,
+	fs::File,
+	io,
+	ops::{BitOr, BitOrAssign, Deref},
+	os::unix::io::AsRawFd,
+};
+
+// file seals, only supported on memfds created with `MmapMutFile::memfd` or `anonymous`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Seals(libc::c_int);
+
+impl Seals {
+	pub const SHRINK: Seals = Seals(libc::F_SEAL_SHRINK);
+	pub const GROW: Seals = Seals(libc::F_SEAL_GROW);
+	// fails while any writable shared map of the file exists, including a `MmapMutFile`
+	pub const WRITE: Seals = Seals(libc::F_SEAL_WRITE);
+	// prevents any further seals from being added
+	pub const SEAL: Seals = Seals(libc::F_SEAL_SEAL);
+
+	pub fn empty() -> Self {
+		Seals(0)
+	}
+
+	pub fn contains(self, other: Seals) -> bool {
+		self.0 & other.0 == other.0
+	}
+
+	pub fn intersects(self, other: Seals) -> bool {
+		self.0 & other.0 != 0
+	}
+}
+
+impl fmt::Debug for Seals {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let names = [
+			(Seals::SHRINK, "SHRINK"),
+			(Seals::GROW, "GROW"),
+			(Seals::WRITE, "WRITE"),
+			(Seals::SEAL, "SEAL"),
+		];
+
+		let set: Vec<_> = names
+			.iter()
+			.filter(|&&(seal, _)| self.contains(seal))
+			.map(|&(_, name)| name)
+			.collect();
+
+		write!(f, "Seals({})", set.join(" | "))
+	}
+}
+
+impl BitOr for Seals {
+	type Output = Seals;
+
+	fn bitor(self, rhs: Seals) -> Seals {
+		Seals(self.0 | rhs.0)
+	}
+}
+
+impl BitOrAssign for Seals {
+	fn bitor_assign(&mut self, rhs: Seals) {
+		self.0 |= rhs.0;
+	}
+}
+
+impl<M> MmappedFile<M>
+where
+	M: AsRef<[u8]> + Deref<Target = [u8]>,
+{
+	// `Seals::WRITE` fails while the file is mapped writable, use `seal_read_only` for those
+	pub fn seal(&self, seals: Seals) -> io::Result<()> {
+		add_seals(&self.file, seals)
+	}
+
+	pub fn seals(&self) -> io::Result<Seals> {
+		get_seals(&self.file)
+	}
+}
+
+impl MmappedFile<MmapMut> {
+	// unmaps the writable map first, so the file can be sealed with `Seals::WRITE`, and maps it
+	// again read-only
+	pub fn seal_read_only(self, seals: Seals) -> io::Result<MmappedFile<Mmap>> {
+		let MmappedFile { file, map, offset } = self;
+		let len = map.len();
+		drop(map);
+
+		add_seals(&file, seals)?;
+
+		let map = unsafe { memmap::MmapOptions::new().offset(offset).len(len).map(&file) }?;
+
+		Ok(MmappedFile { file, map, offset })
+	}
+}
+
+fn add_seals(file: &File, seals: Seals) -> io::Result<()> {
+	log::trace!("sealing file with {:?}", seals);
+
+	if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_ADD_SEALS, seals.0) } != 0 {
+		let e = io::Error::last_os_error();
+
+		return Err(match e.raw_os_error() {
+			Some(libc::EBUSY) => io::Error::new(
+				e.kind(),
+				format!("cannot seal file against writes while it is mapped writable ({})", e),
+			),
+			_ => e,
+		});
+	}
+
+	Ok(())
+}
+
+fn get_seals(file: &File) -> io::Result<Seals> {
+	let seals = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GET_SEALS) };
+
+	if seals < 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	Ok(Seals(seals))
+}
+
+// turns the EPERM from resizing a sealed file into something more descriptive
+pub(crate) fn resize_error(file: &File, e: io::Error) -> io::Error {
+	if e.raw_os_error() != Some(libc::EPERM) {
+		return e;
+	}
+
+	match get_seals(file) {
+		Ok(seals) if seals.intersects(Seals::GROW | Seals::SHRINK) => io::Error::new(
+			io::ErrorKind::PermissionDenied,
+			format!("cannot resize file sealed with {:?}", seals),
+		),
+		_ => e,
+	}
+}
@@ -0,0 +1,159 @@
+use crate::MmapFile;
+
+use std::{
+	fmt,
+	ops::{Bound, Deref, Range, RangeBounds},
+	sync::Arc,
+};
+
+// a cheaply clonable handle to a read-only map, for sharing it between threads
+#[derive(Clone)]
+pub struct SharedMmap {
+	inner: Arc<MmapFile>,
+}
+
+impl SharedMmap {
+	pub fn new(file: MmapFile) -> Self {
+		Self {
+			inner: Arc::new(file),
+		}
+	}
+
+	// panics if `range` is out of bounds, like slicing does
+	pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> MmapSlice {
+		let range = to_range(range, self.inner.map.len());
+
+		MmapSlice {
+			inner: Arc::clone(&self.inner),
+			range,
+		}
+	}
+
+	pub fn get_ref(&self) -> &MmapFile {
+		&self.inner
+	}
+}
+
+// an owned sub-slice of a `SharedMmap`, keeping the map alive for as long as it exists
+#[derive(Clone)]
+pub struct MmapSlice {
+	inner: Arc<MmapFile>,
+	range: Range<usize>,
+}
+
+impl MmapSlice {
+	// `range` is relative to this slice, panics if it is out of bounds
+	pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> MmapSlice {
+		let range = to_range(range, self.range.len());
+
+		MmapSlice {
+			inner: Arc::clone(&self.inner),
+			range: self.range.start + range.start..self.range.start + range.end,
+		}
+	}
+
+	// offset of the slice from the start of the map
+	pub fn offset(&self) -> usize {
+		self.range.start
+	}
+}
+
+impl MmapFile {
+	pub fn into_shared(self) -> SharedMmap {
+		SharedMmap::new(self)
+	}
+}
+
+impl From<MmapFile> for SharedMmap {
+	fn from(file: MmapFile) -> Self {
+		SharedMmap::new(file)
+	}
+}
+
+impl From<SharedMmap> for MmapSlice {
+	fn from(shared: SharedMmap) -> Self {
+		shared.slice(..)
+	}
+}
+
+#[cfg(feature = "bytes")]
+impl From<MmapSlice> for bytes::Bytes {
+	fn from(slice: MmapSlice) -> Self {
+		bytes::Bytes::from_owner(slice)
+	}
+}
+
+#[cfg(feature = "bytes")]
+impl From<SharedMmap> for bytes::Bytes {
+	fn from(shared: SharedMmap) -> Self {
+		bytes::Bytes::from_owner(shared)
+	}
+}
+
+fn to_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+	let start = match range.start_bound() {
+		Bound::Included(&n) => n,
+		Bound::Excluded(&n) => n.checked_add(1).expect("range start overflows"),
+		Bound::Unbounded => 0,
+	};
+
+	let end = match range.end_bound() {
+		Bound::Included(&n) => n.checked_add(1).expect("range end overflows"),
+		Bound::Excluded(&n) => n,
+		Bound::Unbounded => len,
+	};
+
+	assert!(
+		start <= end && end <= len,
+		"range {}..{} is out of bounds for slice of {} bytes",
+		start,
+		end,
+		len
+	);
+
+	start..end
+}
+
+impl Deref for SharedMmap {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		&self.inner
+	}
+}
+
+impl AsRef<[u8]> for SharedMmap {
+	fn as_ref(&self) -> &[u8] {
+		&self.inner
+	}
+}
+
+impl Deref for MmapSlice {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		&self.inner[self.range.clone()]
+	}
+}
+
+impl AsRef<[u8]> for MmapSlice {
+	fn as_ref(&self) -> &[u8] {
+		self
+	}
+}
+
+impl fmt::Debug for SharedMmap {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SharedMmap")
+			.field("len", &self.len())
+			.finish()
+	}
+}
+
+impl fmt::Debug for MmapSlice {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("MmapSlice")
+			.field("range", &self.range)
+			.finish()
+	}
+}
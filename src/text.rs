@@ -0,0 +1,102 @@
+use crate::MmappedFile;
+
+use std::{ops::Deref, str::Utf8Error};
+
+impl<M> MmappedFile<M>
+where
+	M: AsRef<[u8]> + Deref<Target = [u8]>,
+{
+	pub fn as_str(&self) -> Result<&str, Utf8Error> {
+		from_utf8(&self.map)
+	}
+
+	pub fn lines(&self) -> Result<std::str::Lines<'_>, Utf8Error> {
+		self.as_str().map(str::lines)
+	}
+
+	pub fn line_index(&self) -> Result<LineIndex<'_>, Utf8Error> {
+		self.as_str().map(LineIndex::new)
+	}
+}
+
+// with the simdutf8 feature only invalid input pays for the slower std validation, which is
+// still needed to produce a std `Utf8Error`
+#[cfg(feature = "simdutf8")]
+fn from_utf8(bytes: &[u8]) -> Result<&str, Utf8Error> {
+	match simdutf8::basic::from_utf8(bytes) {
+		Ok(s) => Ok(s),
+		Err(_) => std::str::from_utf8(bytes),
+	}
+}
+
+#[cfg(not(feature = "simdutf8"))]
+fn from_utf8(bytes: &[u8]) -> Result<&str, Utf8Error> {
+	std::str::from_utf8(bytes)
+}
+
+// line start offsets of a text, so any line can be looked up in O(1), lines are split the
+// same way as `str::lines`
+#[derive(Clone, Debug)]
+pub struct LineIndex<'a> {
+	text: &'a str,
+	starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+	pub fn new(text: &'a str) -> Self {
+		let mut starts = Vec::new();
+
+		if !text.is_empty() {
+			starts.push(0);
+		}
+
+		// a trailing newline doesn't start another line
+		starts.extend(
+			text.match_indices('\n')
+				.map(|(i, _)| i + 1)
+				.filter(|&start| start < text.len()),
+		);
+
+		Self { text, starts }
+	}
+
+	pub fn len(&self) -> usize {
+		self.starts.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.starts.is_empty()
+	}
+
+	// the line without its line ending
+	pub fn line(&self, n: usize) -> Option<&'a str> {
+		let start = *self.starts.get(n)?;
+		let end = self.starts.get(n + 1).copied().unwrap_or(self.text.len());
+
+		let line = &self.text[start..end];
+		// a lone '\r' isn't a line ending, only one directly before the '\n' is
+		let line = match line.strip_suffix('\n') {
+			Some(line) => line.strip_suffix('\r').unwrap_or(line),
+			None => line,
+		};
+
+		Some(line)
+	}
+
+	// byte offset of the start of line `n`
+	pub fn line_start(&self, n: usize) -> Option<usize> {
+		self.starts.get(n).copied()
+	}
+
+	// the line containing byte `offset`
+	pub fn line_of(&self, offset: usize) -> Option<usize> {
+		if offset >= self.text.len() {
+			return None;
+		}
+
+		match self.starts.binary_search(&offset) {
+			Ok(n) => Some(n),
+			Err(n) => Some(n - 1),
+		}
+	}
+}
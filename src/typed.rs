@@ -0,0 +1,78 @@
+use crate::{pod::bytes_of_slice, MmapFile, MmappedWriter, Pod};
+
+use std::{
+	io::{self, Write},
+	marker::PhantomData,
+	mem, slice,
+};
+
+// appends `T` records through a `MmappedWriter`, without unsafe casts at every call site
+pub struct TypedWriter<T: Pod> {
+	inner: MmappedWriter,
+	// byte offset of the first record
+	start: usize,
+	len: usize,
+	record: PhantomData<T>,
+}
+
+impl<T: Pod> TypedWriter<T> {
+	// pads the writer with zeros up to `T`'s alignment, so the records can be viewed in place
+	pub fn new(mut inner: MmappedWriter) -> io::Result<Self> {
+		// aligned by file offset, which the map's address shares modulo the page size, as the
+		// map itself need not start at a page aligned offset
+		let align = mem::align_of::<T>() as u64;
+		let offset = inner.inner.offset + inner.position() as u64;
+		let padding = ((align - offset % align) % align) as usize;
+
+		inner.write_all(&vec![0; padding])?;
+
+		let start = inner.position();
+
+		Ok(Self {
+			inner,
+			start,
+			len: 0,
+			record: PhantomData,
+		})
+	}
+
+	pub fn push(&mut self, value: &T) -> io::Result<()> {
+		self.extend(slice::from_ref(value))
+	}
+
+	pub fn extend(&mut self, values: &[T]) -> io::Result<()> {
+		self.inner.write_all(bytes_of_slice(values))?;
+		self.len += values.len();
+
+		Ok(())
+	}
+
+	// number of records written
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn start(&self) -> usize {
+		self.start
+	}
+
+	// the records are at `view_slice::<T>(start, len)`, or simply `as_slice_of::<T>()`
+	// when the writer started at the beginning of the file
+	pub fn finish(self) -> io::Result<MmapFile> {
+		self.inner.finish_read_only()
+	}
+
+	pub fn into_inner(self) -> MmappedWriter {
+		self.inner
+	}
+}
+
+impl MmappedWriter {
+	pub fn into_typed<T: Pod>(self) -> io::Result<TypedWriter<T>> {
+		TypedWriter::new(self)
+	}
+}
@@ -0,0 +1,290 @@
+use crate::{
+	capacity_overflow, invalid_data,
+	pod::{cast_slice, cast_slice_mut},
+	GrowthPolicy, MmapFile, MmapMutFile, Pod,
+};
+
+use std::{
+	cmp, io,
+	marker::PhantomData,
+	mem,
+	ops::{Deref, DerefMut},
+	path::Path,
+};
+
+const MAGIC: u64 = u64::from_le_bytes(*b"MMAPVEC1");
+const DEFAULT_CAPACITY: usize = 64;
+
+// magic, element size, length, reserved
+type Header = [u64; 4];
+
+// a `Vec<T>` backed by a file, the length is kept in a small header in front of the elements
+// so reopening the file restores the contents
+pub struct MmapVec<T: Pod> {
+	inner: MmapMutFile,
+	len: usize,
+	elem: PhantomData<T>,
+}
+
+impl<T: Pod> MmapVec<T> {
+	pub unsafe fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		Self::with_capacity(path, DEFAULT_CAPACITY)
+	}
+
+	// creates the file, replacing any existing one
+	pub unsafe fn with_capacity<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Self> {
+		if mem::size_of::<T>() == 0 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"zero-sized types can't be stored in a MmapVec",
+			));
+		}
+
+		let size = Self::file_size(capacity)?;
+
+		let inner = MmapFile::options()
+			.read_write()
+			.create(true)
+			.truncate(true)
+			.size(size as u64)
+			.open(path)?;
+
+		let mut vec = Self {
+			inner,
+			len: 0,
+			elem: PhantomData,
+		};
+
+		*vec.header_mut() = [MAGIC, mem::size_of::<T>() as u64, 0, 0];
+
+		Ok(vec)
+	}
+
+	pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let inner = MmapMutFile::open_rw(path)?;
+
+		let header = inner
+			.view::<Header>(0)
+			.map_err(|_| invalid_data("file is too small for a MmapVec header"))?;
+		let [magic, elem_size, len, _] = *header;
+
+		if magic != MAGIC {
+			return Err(invalid_data("file is not a MmapVec"));
+		}
+
+		if elem_size != mem::size_of::<T>() as u64 {
+			return Err(invalid_data(format!(
+				"MmapVec elements are {} bytes, expected {}",
+				elem_size,
+				mem::size_of::<T>()
+			)));
+		}
+
+		// the elements start after the header, at `T`'s alignment, which may be past its end
+		if inner.map.len() < Self::data_offset() {
+			return Err(invalid_data("file is too small for the MmapVec elements"));
+		}
+
+		let mut vec = Self {
+			inner,
+			len: 0,
+			elem: PhantomData,
+		};
+
+		if len > vec.capacity() as u64 {
+			return Err(invalid_data(format!(
+				"MmapVec length {} is larger than the file allows",
+				len
+			)));
+		}
+
+		vec.len = len as usize;
+
+		Ok(vec)
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn capacity(&self) -> usize {
+		(self.inner.map.len() - Self::data_offset()) / mem::size_of::<T>()
+	}
+
+	// grows the file so at least `additional` more elements fit
+	pub fn reserve(&mut self, additional: usize) -> io::Result<()> {
+		let required = self
+			.len
+			.checked_add(additional)
+			.ok_or_else(capacity_overflow)?;
+
+		if required <= self.capacity() {
+			return Ok(());
+		}
+
+		let required_len = Self::file_size(required)?;
+		let new_len = GrowthPolicy::Doubling.next_len(self.inner.map.len(), required_len);
+
+		log::trace!("growing MmapVec to {} bytes", new_len);
+
+		self.inner.resize(new_len as u64)
+	}
+
+	pub fn push(&mut self, value: T) -> io::Result<()> {
+		self.reserve(1)?;
+
+		let len = self.len;
+		self.data_mut()[len] = value;
+		self.set_len(len + 1);
+
+		Ok(())
+	}
+
+	pub fn pop(&mut self) -> Option<T> {
+		let value = *self.last()?;
+		self.set_len(self.len - 1);
+
+		Some(value)
+	}
+
+	// panics if `index > len`, like `Vec::insert`
+	pub fn insert(&mut self, index: usize, value: T) -> io::Result<()> {
+		assert!(
+			index <= self.len,
+			"insertion index (is {}) should be <= len (is {})",
+			index,
+			self.len
+		);
+
+		self.reserve(1)?;
+
+		let len = self.len;
+		let data = self.data_mut();
+		data.copy_within(index..len, index + 1);
+		data[index] = value;
+		self.set_len(len + 1);
+
+		Ok(())
+	}
+
+	// panics if `index >= len`, like `Vec::remove`
+	pub fn remove(&mut self, index: usize) -> T {
+		assert!(
+			index < self.len,
+			"removal index (is {}) should be < len (is {})",
+			index,
+			self.len
+		);
+
+		let len = self.len;
+		let data = self.data_mut();
+		let value = data[index];
+		data.copy_within(index + 1..len, index);
+		self.set_len(len - 1);
+
+		value
+	}
+
+	pub fn truncate(&mut self, len: usize) {
+		self.set_len(cmp::min(len, self.len));
+	}
+
+	pub fn clear(&mut self) {
+		self.set_len(0);
+	}
+
+	pub fn as_slice(&self) -> &[T] {
+		&self.data()[..self.len]
+	}
+
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		let len = self.len;
+		&mut self.data_mut()[..len]
+	}
+
+	pub fn flush(&self) -> io::Result<()> {
+		self.inner.flush()
+	}
+
+	// shrinks the file to fit the elements and hands it back
+	pub fn into_inner(mut self) -> io::Result<MmapMutFile> {
+		let size = Self::file_size(self.len)?;
+		self.inner.resize(size as u64)?;
+
+		Ok(self.inner)
+	}
+
+	fn file_size(capacity: usize) -> io::Result<usize> {
+		capacity
+			.checked_mul(mem::size_of::<T>())
+			.and_then(|size| size.checked_add(Self::data_offset()))
+			.ok_or_else(capacity_overflow)
+	}
+
+	fn data_offset() -> usize {
+		let align = mem::align_of::<T>();
+		mem::size_of::<Header>().div_ceil(align) * align
+	}
+
+	fn header_mut(&mut self) -> &mut Header {
+		self.inner
+			.view_mut::<Header>(0)
+			.expect("MmapVec header is always in bounds and aligned")
+	}
+
+	// the whole capacity, the map is page aligned and the data offset aligned to `T`
+	fn data(&self) -> &[T] {
+		let bytes = &self.inner.map[Self::data_offset()..];
+		let bytes = &bytes[..bytes.len() - bytes.len() % mem::size_of::<T>()];
+
+		cast_slice(bytes).expect("MmapVec data is always aligned")
+	}
+
+	fn data_mut(&mut self) -> &mut [T] {
+		let bytes = &mut self.inner.map[Self::data_offset()..];
+		let len = bytes.len() - bytes.len() % mem::size_of::<T>();
+
+		cast_slice_mut(&mut bytes[..len]).expect("MmapVec data is always aligned")
+	}
+
+	fn set_len(&mut self, len: usize) {
+		self.len = len;
+		self.header_mut()[2] = len as u64;
+	}
+}
+
+impl<T: Pod> Deref for MmapVec<T> {
+	type Target = [T];
+
+	fn deref(&self) -> &[T] {
+		self.as_slice()
+	}
+}
+
+impl<T: Pod> DerefMut for MmapVec<T> {
+	fn deref_mut(&mut self) -> &mut [T] {
+		self.as_mut_slice()
+	}
+}
+
+impl<'a, T: Pod> IntoIterator for &'a MmapVec<T> {
+	type Item = &'a T;
+	type IntoIter = std::slice::Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'a, T: Pod> IntoIterator for &'a mut MmapVec<T> {
+	type Item = &'a mut T;
+	type IntoIter = std::slice::IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
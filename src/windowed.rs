@@ -0,0 +1,148 @@
+use crate::page_size;
+
+use memmap::Mmap;
+
+use std::{convert::TryFrom, fs::File, io, ops::Range, path::Path};
+
+struct Window {
+	// page aligned offset of the window in the file
+	offset: u64,
+	map: Mmap,
+}
+
+impl Window {
+	fn contains(&self, range: &Range<u64>) -> bool {
+		self.offset <= range.start && range.end <= self.offset + self.map.len() as u64
+	}
+}
+
+// maps a file a window at a time, so files larger than the address space can still be read
+pub struct WindowedMmap {
+	file: File,
+	file_len: u64,
+	window_size: usize,
+	max_windows: usize,
+	// least recently used first
+	windows: Vec<Window>,
+}
+
+impl WindowedMmap {
+	pub unsafe fn open<P: AsRef<Path>>(
+		path: P,
+		window_size: usize,
+		max_windows: usize,
+	) -> io::Result<Self> {
+		log::info!(
+			"Opening windowed memory mapped file '{}'",
+			path.as_ref().display()
+		);
+
+		Self::new(File::open(path)?, window_size, max_windows)
+	}
+
+	// `window_size` is rounded up to a multiple of the page size
+	pub unsafe fn new(file: File, window_size: usize, max_windows: usize) -> io::Result<Self> {
+		if window_size == 0 || max_windows == 0 {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"window size and window count must be non-zero",
+			));
+		}
+
+		let page_size = page_size();
+		let window_size = window_size
+			.checked_add(page_size - 1)
+			.map(|size| size / page_size * page_size)
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "window size too large"))?;
+
+		let file_len = file.metadata()?.len();
+
+		log::debug!(
+			"windowed mmap over {} bytes, {} windows of {} bytes",
+			file_len,
+			max_windows,
+			window_size
+		);
+
+		Ok(Self {
+			file,
+			file_len,
+			window_size,
+			max_windows,
+			windows: Vec::with_capacity(max_windows),
+		})
+	}
+
+	pub fn len(&self) -> u64 {
+		self.file_len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.file_len == 0
+	}
+
+	pub fn window_size(&self) -> usize {
+		self.window_size
+	}
+
+	// returns the bytes in `range`, remapping the least recently used window if no current
+	// window covers it, ranges larger than the window size get a window of their own size
+	pub fn get(&mut self, range: Range<u64>) -> io::Result<&[u8]> {
+		if range.start > range.end || range.end > self.file_len {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				format!(
+					"range {}..{} is out of bounds for file of {} bytes",
+					range.start, range.end, self.file_len
+				),
+			));
+		}
+
+		if range.start == range.end {
+			return Ok(&[]);
+		}
+
+		let idx = match self.windows.iter().position(|w| w.contains(&range)) {
+			Some(idx) => {
+				// move to the back, as the most recently used
+				let window = self.windows.remove(idx);
+				self.windows.push(window);
+				self.windows.len() - 1
+			}
+			None => {
+				if self.windows.len() == self.max_windows {
+					self.windows.remove(0);
+				}
+
+				let window = self.map_window(&range)?;
+				self.windows.push(window);
+				self.windows.len() - 1
+			}
+		};
+
+		let window = &self.windows[idx];
+		let start = (range.start - window.offset) as usize;
+		let end = (range.end - window.offset) as usize;
+
+		Ok(&window.map[start..end])
+	}
+
+	fn map_window(&self, range: &Range<u64>) -> io::Result<Window> {
+		let offset = range.start - range.start % page_size() as u64;
+		let len = std::cmp::max(self.window_size as u64, range.end - offset);
+		let len = std::cmp::min(len, self.file_len - offset);
+
+		let len = usize::try_from(len).map_err(|_| {
+			io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"range is larger than the address space",
+			)
+		})?;
+
+		log::trace!("mapping window of {} bytes at offset {}", len, offset);
+
+		let map = unsafe { memmap::MmapOptions::new().offset(offset).len(len).map(&self.file) }?;
+
+		Ok(Window { offset, map })
+	}
+}
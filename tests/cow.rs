@@ -0,0 +1,23 @@
+#![cfg(unix)]
+
+use mmap_file::{Advice, MmapFile};
+
+use std::{fs, io};
+
+#[test]
+fn dont_need_is_rejected_for_copy_on_write_maps() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("cow");
+	fs::write(&path, [b'a'; 4096]).unwrap();
+
+	let mut file = unsafe { MmapFile::open_private(&path) }.unwrap();
+	file[0] = b'z';
+
+	let bytes: &[u8] = &file;
+	let e = file.advise(Advice::DontNeed).unwrap_err();
+	assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+	assert_eq!(bytes[0], b'z');
+
+	file.advise(Advice::Sequential).unwrap();
+	assert_eq!(fs::read(&path).unwrap()[0], b'a');
+}
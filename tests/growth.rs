@@ -0,0 +1,46 @@
+use mmap_file::{GrowthPolicy, MmapMutFile};
+
+use std::io::Write;
+
+#[test]
+fn growth_from_zero_length_fits_required() {
+	let policies = [
+		GrowthPolicy::Doubling,
+		GrowthPolicy::Increment(0),
+		GrowthPolicy::CappedDoubling(0),
+		GrowthPolicy::ExactFit,
+	];
+
+	for policy in &policies {
+		assert_eq!(policy.next_len(0, 1), 1, "{:?}", policy);
+		assert_eq!(policy.next_len(0, 4096), 4096, "{:?}", policy);
+	}
+}
+
+#[test]
+fn growth_policies() {
+	assert_eq!(GrowthPolicy::Doubling.next_len(100, 101), 200);
+	assert_eq!(GrowthPolicy::Increment(10).next_len(100, 101), 110);
+	assert_eq!(GrowthPolicy::CappedDoubling(50).next_len(100, 101), 150);
+	assert_eq!(GrowthPolicy::CappedDoubling(500).next_len(100, 101), 200);
+	assert_eq!(GrowthPolicy::ExactFit.next_len(100, 101), 101);
+	assert_eq!(GrowthPolicy::Doubling.next_len(usize::MAX, usize::MAX), usize::MAX);
+}
+
+#[test]
+fn very_large_write_grows_to_fit() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("large");
+
+	let file = unsafe { MmapMutFile::create_with_size(&path, 16) }.unwrap();
+	let mut writer = file.into_writer().with_growth_policy(GrowthPolicy::Increment(1));
+
+	let buf = vec![0xab; 1 << 20];
+	assert_eq!(writer.write(&buf).unwrap(), buf.len());
+	assert_eq!(writer.len(), buf.len());
+
+	drop(writer);
+
+	let contents = std::fs::read(&path).unwrap();
+	assert_eq!(contents, buf);
+}
@@ -0,0 +1,109 @@
+use mmap_file::{MmapHashMap, MmapHashMapView};
+
+use std::{collections::HashMap, convert::TryInto, fs, io};
+
+// xorshift64, enough to drive the fuzz test without pulling in a rng crate
+struct Rng(u64);
+
+impl Rng {
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+}
+
+fn assert_same(map: &MmapHashMap<u64, u32>, expected: &HashMap<u64, u32>) {
+	assert_eq!(map.len(), expected.len());
+
+	for (key, value) in expected {
+		assert_eq!(map.get(key), Some(value), "key {}", key);
+	}
+
+	let mut entries: Vec<_> = map.iter().map(|(&k, &v)| (k, v)).collect();
+	entries.sort_unstable();
+	let mut expected: Vec<_> = expected.iter().map(|(&k, &v)| (k, v)).collect();
+	expected.sort_unstable();
+	assert_eq!(entries, expected);
+}
+
+#[test]
+fn fuzz_against_std_hashmap() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("fuzz");
+
+	let mut map = unsafe { MmapHashMap::<u64, u32>::with_capacity(&path, 1) }.unwrap();
+	let mut expected = HashMap::new();
+	let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+	// few enough keys that removes hit long probe runs, exercising the backward shift
+	for i in 0..20_000u32 {
+		let key = rng.next() % 600;
+
+		if rng.next().is_multiple_of(3) {
+			assert_eq!(map.remove(&key), expected.remove(&key), "remove {}", key);
+		} else {
+			assert_eq!(map.insert(key, i).unwrap(), expected.insert(key, i), "insert {}", key);
+		}
+
+		assert_eq!(map.get(&key), expected.get(&key));
+
+		if i.is_multiple_of(1000) {
+			assert_same(&map, &expected);
+		}
+	}
+
+	assert_same(&map, &expected);
+	assert!(map.capacity() >= expected.len());
+	map.sync_all().unwrap();
+	drop(map);
+
+	// nothing of the rehash may be left behind
+	assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+	let map = unsafe { MmapHashMap::<u64, u32>::open(&path) }.unwrap();
+	assert_same(&map, &expected);
+
+	let view = unsafe { MmapHashMapView::<u64, u32>::open(&path) }.unwrap();
+	assert_eq!(view.len(), expected.len());
+	for key in 0..600 {
+		assert_eq!(view.get(&key), expected.get(&key), "key {}", key);
+	}
+	assert_eq!(view.iter().count(), expected.len());
+}
+
+#[test]
+fn open_with_other_types_fails() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("types");
+
+	let mut map = unsafe { MmapHashMap::<u64, u32>::create(&path) }.unwrap();
+	map.insert(1, 2).unwrap();
+	drop(map);
+
+	let e = unsafe { MmapHashMap::<u32, u32>::open(&path) }.err().unwrap();
+	assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+	let e = unsafe { MmapHashMapView::<u64, u64>::open(&path) }.err().unwrap();
+	assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn corrupt_table_without_empty_slots_terminates() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("corrupt");
+
+	drop(unsafe { MmapHashMap::<u64, u32>::with_capacity(&path, 4) }.unwrap());
+
+	// mark every slot occupied, while the header still claims the map is empty
+	let mut contents = fs::read(&path).unwrap();
+	let slots = u64::from_le_bytes(contents[32..40].try_into().unwrap()) as usize;
+	contents[64..64 + slots].iter_mut().for_each(|state| *state = 1);
+	fs::write(&path, &contents).unwrap();
+
+	let mut map = unsafe { MmapHashMap::<u64, u32>::open(&path) }.unwrap();
+	assert_eq!(map.get(&1), None);
+	assert_eq!(map.insert(1, 1).unwrap_err().kind(), io::ErrorKind::InvalidData);
+	assert_eq!(map.remove(&1), None);
+}
@@ -0,0 +1,22 @@
+use mmap_file::MmapFile;
+
+use std::{fs, io};
+
+#[test]
+fn out_of_bounds_ranges_are_rejected() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("bounds");
+	fs::write(&path, [0; 100]).unwrap();
+
+	let ranges = [(0, 101), (101, 0), (50, 51), (u64::MAX, 2), (u64::MAX, 0)];
+
+	for &(offset, len) in &ranges {
+		let e = unsafe { MmapFile::options().offset(offset).len(len).open(&path) }
+			.err()
+			.unwrap();
+		assert_eq!(e.kind(), io::ErrorKind::InvalidInput, "{}..+{}", offset, len);
+	}
+
+	let file = unsafe { MmapFile::options().offset(50).len(50).open(&path) }.unwrap();
+	assert_eq!(file.map_len(), 50);
+}
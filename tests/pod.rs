@@ -0,0 +1,93 @@
+use mmap_file::{MmapFile, MmapMutFile, ViewError};
+
+use std::{fs, io};
+
+fn file_of(bytes: &[u8]) -> (tempfile::TempDir, MmapMutFile) {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("pod");
+	fs::write(&path, bytes).unwrap();
+
+	let file = unsafe { MmapFile::options().read_write().open(&path) }.unwrap();
+	(dir, file)
+}
+
+#[test]
+fn views_read_and_write_in_place() {
+	let mut bytes = Vec::new();
+	for value in &[1u32, 2, 3, 4] {
+		bytes.extend_from_slice(&value.to_ne_bytes());
+	}
+	let (_dir, mut file) = file_of(&bytes);
+
+	assert_eq!(file.as_slice_of::<u32>().unwrap(), &[1, 2, 3, 4]);
+	assert_eq!(*file.view::<u32>(4).unwrap(), 2);
+	assert_eq!(file.view_slice::<u32>(8, 2).unwrap(), &[3, 4]);
+	assert_eq!(file.view_slice::<u32>(16, 0).unwrap(), &[] as &[u32]);
+
+	*file.view_mut::<u32>(0).unwrap() = 10;
+	file.view_slice_mut::<u32>(8, 2).unwrap().copy_from_slice(&[30, 40]);
+	assert_eq!(file.as_mut_slice_of::<u32>().unwrap(), &[10, 2, 30, 40]);
+	assert_eq!(file.view::<[u16; 2]>(4).unwrap(), &[2, 0]);
+}
+
+#[test]
+fn view_errors() {
+	let (_dir, mut file) = file_of(&[0; 10]);
+
+	assert!(matches!(
+		file.view::<u32>(1),
+		Err(ViewError::Misaligned { align: 4, .. })
+	));
+	assert_eq!(
+		file.as_slice_of::<u32>(),
+		Err(ViewError::Length { len: 10, size: 4 })
+	);
+	assert_eq!(
+		file.view::<u64>(4),
+		Err(ViewError::TooShort { len: 6, required: 8 })
+	);
+	assert_eq!(
+		file.view_slice::<u16>(4, 4),
+		Err(ViewError::TooShort { len: 6, required: 8 })
+	);
+	assert_eq!(
+		file.view_slice::<u16>(20, 1),
+		Err(ViewError::TooShort { len: 0, required: 2 })
+	);
+	assert_eq!(
+		file.view_slice_mut::<u32>(0, 3).err(),
+		Some(ViewError::TooShort { len: 10, required: 12 })
+	);
+	assert_eq!(file.view::<[u8; 0]>(0), Err(ViewError::ZeroSized));
+
+	let e = io::Error::from(file.view::<u64>(8).unwrap_err());
+	assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+	assert_eq!(e.to_string(), "8 bytes are needed but only 2 are left");
+}
+
+#[cfg(feature = "bytemuck")]
+mod bytemuck_types {
+	use mmap_file::{bytemuck, impl_pod_via_bytemuck};
+
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	#[repr(C)]
+	struct Pair {
+		a: u32,
+		b: u32,
+	}
+
+	unsafe impl bytemuck::Zeroable for Pair {}
+	unsafe impl bytemuck::Pod for Pair {}
+
+	impl_pod_via_bytemuck!(Pair);
+
+	#[test]
+	fn bytemuck_types_can_be_viewed() {
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(&1u32.to_ne_bytes());
+		bytes.extend_from_slice(&2u32.to_ne_bytes());
+		let (_dir, file) = super::file_of(&bytes);
+
+		assert_eq!(file.view::<Pair>(0).unwrap(), &Pair { a: 1, b: 2 });
+	}
+}
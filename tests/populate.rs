@@ -0,0 +1,23 @@
+#![cfg(unix)]
+
+use mmap_file::{MmapFile, MmapMutFile, Populate};
+
+use std::{fs, os::unix::fs::MetadataExt};
+
+#[test]
+fn populating_a_writable_map_leaves_holes_alone() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("sparse");
+
+	drop(unsafe { MmapMutFile::create_with_size(&path, 16 << 20) }.unwrap());
+	let blocks = fs::metadata(&path).unwrap().blocks();
+
+	let options = MmapFile::options()
+		.read_write()
+		.populate(Populate::Blocking);
+	let file = unsafe { options.open(&path) }.unwrap();
+	file.flush().unwrap();
+	drop(file);
+
+	assert_eq!(fs::metadata(&path).unwrap().blocks(), blocks);
+}
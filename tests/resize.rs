@@ -0,0 +1,85 @@
+use mmap_file::{MmapFile, MmapMutFile};
+
+use std::{fs, io::Write};
+
+fn sub_range_file(path: &std::path::Path) {
+	let contents: Vec<u8> = (0..8192).map(|i| i as u8).collect();
+	fs::write(path, &contents).unwrap();
+}
+
+#[test]
+fn write_at_on_sub_range_keeps_the_tail() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("sub_range");
+	sub_range_file(&path);
+
+	let mut file = unsafe { MmapFile::options().read_write().len(100).open(&path) }.unwrap();
+	assert_eq!(file.write_at(&[1; 10], 200).unwrap(), 10);
+	assert_eq!(file.map_len(), 210);
+	drop(file);
+
+	let contents = fs::read(&path).unwrap();
+	assert_eq!(contents.len(), 8192);
+	assert_eq!(&contents[200..210], &[1; 10]);
+	assert_eq!(contents[8191], 8191u32 as u8);
+}
+
+#[test]
+fn resize_past_end_of_sub_range_grows_file() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("sub_range");
+	sub_range_file(&path);
+
+	let options = MmapFile::options().read_write().offset(4096).len(100);
+	let mut file = unsafe { options.open(&path) }.unwrap();
+	file.resize(8192).unwrap();
+	assert_eq!(file.map_len(), 8192);
+	assert_eq!(file.len().unwrap(), 4096 + 8192);
+}
+
+#[test]
+fn writer_on_sub_range_keeps_the_tail() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("sub_range");
+	sub_range_file(&path);
+
+	let file = unsafe { MmapFile::options().read_write().len(100).open(&path) }.unwrap();
+	let mut writer = file.into_writer();
+	writer.write_all(&[7; 300]).unwrap();
+	drop(writer);
+
+	let contents = fs::read(&path).unwrap();
+	assert_eq!(contents.len(), 8192);
+	assert_eq!(&contents[..300], &[7; 300][..]);
+	assert_eq!(contents[300], 300u32 as u8);
+
+	// a writer that never wrote must not truncate the file either
+	let file = unsafe { MmapFile::options().read_write().len(100).open(&path) }.unwrap();
+	drop(file.into_writer());
+	assert_eq!(fs::metadata(&path).unwrap().len(), 8192);
+}
+
+#[test]
+fn resize_of_whole_file_map_follows_the_map() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("whole");
+	sub_range_file(&path);
+
+	let mut file = unsafe { MmapMutFile::open_rw(&path) }.unwrap();
+	file.resize(100).unwrap();
+	assert_eq!(file.len().unwrap(), 100);
+	file.resize(200).unwrap();
+	assert_eq!(file.len().unwrap(), 200);
+}
+
+#[test]
+fn empty_write_at_does_not_grow() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("empty_write");
+	sub_range_file(&path);
+
+	let mut file = unsafe { MmapMutFile::open_rw(&path) }.unwrap();
+	assert_eq!(file.write_at(&[], 1 << 40).unwrap(), 0);
+	assert_eq!(file.map_len(), 8192);
+	assert_eq!(file.len().unwrap(), 8192);
+}
@@ -0,0 +1,27 @@
+#![cfg(target_os = "linux")]
+
+use mmap_file::{MmapMutFile, Seals};
+
+#[test]
+fn write_seal_fails_while_mapped_writable() {
+	let file = MmapMutFile::memfd("writable", 4096).unwrap();
+
+	assert!(file.seal(Seals::WRITE).is_err());
+	assert!(file.make_read_only().unwrap().seal(Seals::WRITE).is_err());
+}
+
+#[test]
+fn seal_read_only_prevents_further_changes() {
+	let mut file = MmapMutFile::memfd("sealed", 4096).unwrap();
+	file[..5].copy_from_slice(b"hello");
+
+	let seals = Seals::WRITE | Seals::GROW | Seals::SHRINK | Seals::SEAL;
+	let file = file.seal_read_only(seals).unwrap();
+	assert_eq!(&file[..5], b"hello");
+	assert_eq!(file.map_len(), 4096);
+	assert!(file.seals().unwrap().contains(seals));
+
+	// neither a writable map nor further seals can be had anymore
+	assert!(file.seal(Seals::WRITE).is_err());
+	assert!(file.make_mut().is_err());
+}
@@ -0,0 +1,73 @@
+use mmap_file::{LineIndex, MmapFile};
+
+use std::fs;
+
+fn lines(index: &LineIndex) -> Vec<String> {
+	(0..index.len())
+		.map(|n| index.line(n).unwrap().to_owned())
+		.collect()
+}
+
+#[test]
+fn line_index_matches_str_lines() {
+	let texts = [
+		"",
+		"one",
+		"one\n",
+		"one\ntwo",
+		"one\r\ntwo\r\n",
+		"bare\rcarriage\rreturns",
+		"trailing\r",
+		"mixed\r\n\nends\r\r\n",
+		"\n\n",
+		"\r\n",
+	];
+
+	for text in &texts {
+		let index = LineIndex::new(text);
+		let expected: Vec<_> = text.lines().map(str::to_owned).collect();
+		assert_eq!(lines(&index), expected, "{:?}", text);
+		assert_eq!(index.is_empty(), text.is_empty());
+		assert_eq!(index.line(index.len()), None);
+	}
+}
+
+#[test]
+fn line_starts_and_lookup() {
+	let index = LineIndex::new("ab\r\ncd\n\nef");
+
+	assert_eq!(index.len(), 4);
+	assert_eq!(index.line_start(1), Some(4));
+	assert_eq!(index.line_start(3), Some(8));
+	assert_eq!(index.line_start(4), None);
+
+	assert_eq!(index.line_of(0), Some(0));
+	assert_eq!(index.line_of(3), Some(0));
+	assert_eq!(index.line_of(4), Some(1));
+	assert_eq!(index.line_of(7), Some(2));
+	assert_eq!(index.line_of(9), Some(3));
+	assert_eq!(index.line_of(10), None);
+}
+
+#[test]
+fn text_views_of_a_file() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("text");
+	fs::write(&path, "first\r\nsecond\nthird\n").unwrap();
+
+	let file = unsafe { MmapFile::open(&path) }.unwrap();
+	assert_eq!(file.as_str().unwrap(), "first\r\nsecond\nthird\n");
+	assert_eq!(
+		file.lines().unwrap().collect::<Vec<_>>(),
+		["first", "second", "third"]
+	);
+
+	let index = file.line_index().unwrap();
+	assert_eq!(index.len(), 3);
+	assert_eq!(index.line(0), Some("first"));
+
+	fs::write(&path, b"valid \xff invalid").unwrap();
+	let file = unsafe { MmapFile::open(&path) }.unwrap();
+	assert!(file.as_str().is_err());
+	assert!(file.line_index().is_err());
+}
@@ -0,0 +1,64 @@
+use mmap_file::{MmapFile, MmapMutFile};
+
+use std::{fs, io::Write};
+
+#[test]
+fn records_read_back_after_finish() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("records");
+
+	let file = unsafe { MmapMutFile::create(&path) }.unwrap();
+	let mut writer = file.into_writer().into_typed::<u64>().unwrap();
+	assert_eq!(writer.start(), 0);
+
+	let records: Vec<u64> = (0..5000).collect();
+	writer.extend(&records).unwrap();
+	writer.push(&u64::MAX).unwrap();
+	assert_eq!(writer.len(), records.len() + 1);
+
+	let file = writer.finish().unwrap();
+	let values = file.as_slice_of::<u64>().unwrap();
+	assert_eq!(&values[..records.len()], &records[..]);
+	assert_eq!(values[records.len()], u64::MAX);
+}
+
+#[test]
+fn records_are_aligned_on_unaligned_maps() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("unaligned");
+
+	for offset in 1..8 {
+		fs::write(&path, [0; 100]).unwrap();
+
+		let options = MmapFile::options().read_write().offset(offset);
+		let mut writer = unsafe { options.open(&path) }.unwrap().into_writer();
+		writer.write_all(b"x").unwrap();
+
+		let mut writer = writer.into_typed::<u32>().unwrap();
+		writer.extend(&[1, 2, 3]).unwrap();
+		let start = writer.start();
+		assert_eq!((offset + start as u64) % 4, 0, "offset {}", offset);
+
+		let file = writer.finish().unwrap();
+		assert_eq!(file.view_slice::<u32>(start, 3).unwrap(), &[1, 2, 3]);
+	}
+}
+
+#[test]
+fn records_after_a_header_survive_reopening() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("header");
+
+	let mut writer = unsafe { MmapMutFile::create(&path) }.unwrap().into_writer();
+	writer.write_all(b"hdr").unwrap();
+
+	let mut writer = writer.into_typed::<u64>().unwrap();
+	assert_eq!(writer.start(), 8);
+	assert!(writer.is_empty());
+	writer.extend(&[7, 8, 9]).unwrap();
+	drop(writer.finish().unwrap());
+
+	let file = unsafe { MmapFile::open(&path) }.unwrap();
+	assert_eq!(&file[..8], b"hdr\0\0\0\0\0");
+	assert_eq!(file.view_slice::<u64>(8, 3).unwrap(), &[7, 8, 9]);
+}
@@ -0,0 +1,79 @@
+use mmap_file::{MmapVec, Pod};
+
+use std::{fs::OpenOptions, io};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C, align(64))]
+struct Aligned([u8; 64]);
+
+unsafe impl Pod for Aligned {}
+
+#[test]
+fn reopen_restores_contents() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("vec");
+
+	let mut vec = unsafe { MmapVec::<u32>::with_capacity(&path, 4) }.unwrap();
+	let mut expected = Vec::new();
+
+	// enough elements to resize the file a few times
+	for i in 0..1000 {
+		vec.push(i).unwrap();
+		expected.push(i);
+	}
+	assert!(vec.capacity() >= 1000);
+
+	vec.insert(0, 42).unwrap();
+	expected.insert(0, 42);
+	vec.insert(500, 43).unwrap();
+	expected.insert(500, 43);
+	assert_eq!(vec.remove(10), expected.remove(10));
+	assert_eq!(vec.pop(), expected.pop());
+	vec[3] = 7;
+	expected[3] = 7;
+
+	assert_eq!(vec.as_slice(), &expected[..]);
+	vec.flush().unwrap();
+	drop(vec);
+
+	let mut vec = unsafe { MmapVec::<u32>::open(&path) }.unwrap();
+	assert_eq!(vec.len(), expected.len());
+	assert_eq!(vec.as_slice(), &expected[..]);
+
+	vec.truncate(10);
+	drop(vec);
+
+	let vec = unsafe { MmapVec::<u32>::open(&path) }.unwrap();
+	assert_eq!(vec.as_slice(), &expected[..10]);
+}
+
+#[test]
+fn open_with_other_element_size_fails() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("elements");
+
+	let mut vec = unsafe { MmapVec::<u32>::create(&path) }.unwrap();
+	vec.push(1).unwrap();
+	drop(vec);
+
+	let e = unsafe { MmapVec::<u64>::open(&path) }.err().unwrap();
+	assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+	assert_eq!(unsafe { MmapVec::<i32>::open(&path) }.unwrap().as_slice(), &[1]);
+}
+
+#[test]
+fn open_truncated_file_with_overaligned_elements_fails() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("aligned");
+
+	let mut vec = unsafe { MmapVec::<Aligned>::create(&path) }.unwrap();
+	vec.push(Aligned([1; 64])).unwrap();
+	drop(vec);
+
+	// keeps the header, but not the padding up to the first element
+	OpenOptions::new().write(true).open(&path).unwrap().set_len(40).unwrap();
+
+	let e = unsafe { MmapVec::<Aligned>::open(&path) }.err().unwrap();
+	assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,70 @@
+#![cfg(unix)]
+
+use mmap_file::WindowedMmap;
+
+use std::{fs, io};
+
+fn contents() -> Vec<u8> {
+	(0..256 * 1024u32).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn reads_through_evicted_windows() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("windowed");
+	let contents = contents();
+	fs::write(&path, &contents).unwrap();
+
+	let mut windowed = unsafe { WindowedMmap::open(&path, 1, 2) }.unwrap();
+	let window_size = windowed.window_size();
+	assert!(window_size > 0);
+	assert_eq!(windowed.len(), contents.len() as u64);
+
+	// touches more windows than are kept, revisiting ones that must have been evicted
+	let offsets = [0, 5, 3, 7, 0, 63, 1, 5];
+	for &i in &offsets {
+		let start = i * window_size as u64 / 2;
+		let end = (start + 100).min(contents.len() as u64);
+		let bytes = windowed.get(start..end).unwrap();
+		assert_eq!(bytes, &contents[start as usize..end as usize], "offset {}", start);
+	}
+}
+
+#[test]
+fn ranges_larger_than_a_window() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("large_range");
+	let contents = contents();
+	fs::write(&path, &contents).unwrap();
+
+	let mut windowed = unsafe { WindowedMmap::open(&path, 1, 1) }.unwrap();
+	let window_size = windowed.window_size() as u64;
+
+	let ranges = [
+		0..3 * window_size + 1,
+		window_size - 1..window_size + 1,
+		17..contents.len() as u64,
+		0..contents.len() as u64,
+	];
+	for range in ranges.iter().cloned() {
+		let expected = &contents[range.start as usize..range.end as usize];
+		assert_eq!(windowed.get(range.clone()).unwrap(), expected, "{:?}", range);
+	}
+}
+
+#[test]
+fn invalid_ranges() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("invalid");
+	fs::write(&path, [1; 100]).unwrap();
+
+	let mut windowed = unsafe { WindowedMmap::open(&path, 4096, 2) }.unwrap();
+
+	assert_eq!(windowed.get(50..50).unwrap(), &[] as &[u8]);
+	assert_eq!(windowed.get(0..101).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+	let (start, end) = (60, 50);
+	assert_eq!(windowed.get(start..end).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+	assert!(unsafe { WindowedMmap::open(&path, 0, 2) }.is_err());
+	assert!(unsafe { WindowedMmap::open(&path, 4096, 0) }.is_err());
+}
@@ -0,0 +1,178 @@
+use mmap_file::{MmapFile, MmapMutFile};
+
+use std::{
+	fs,
+	io::{IoSlice, Read, Seek, SeekFrom, Write},
+};
+
+#[test]
+fn patch_header_after_streaming_body() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("patched");
+
+	let mut writer = unsafe { MmapMutFile::create(&path) }.unwrap().into_writer();
+	writer.write_all(&[0; 4]).unwrap();
+
+	let body = vec![0xab; 20000];
+	writer.write_all(&body).unwrap();
+
+	writer.seek(SeekFrom::Start(0)).unwrap();
+	writer.write_all(&(body.len() as u32).to_le_bytes()).unwrap();
+	assert_eq!(writer.position(), 4);
+	assert_eq!(writer.len(), 4 + body.len());
+	drop(writer);
+
+	let contents = fs::read(&path).unwrap();
+	assert_eq!(contents.len(), 4 + body.len());
+	assert_eq!(&contents[..4], &(body.len() as u32).to_le_bytes());
+	assert_eq!(&contents[4..], &body[..]);
+}
+
+#[test]
+fn seek_past_map_and_write() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("sparse");
+
+	let mut writer = unsafe { MmapMutFile::create_with_size(&path, 16) }.unwrap().into_writer();
+	writer.write_all(b"head").unwrap();
+	assert_eq!(writer.seek(SeekFrom::Start(100_000)).unwrap(), 100_000);
+	writer.write_all(b"tail").unwrap();
+	assert_eq!(writer.len(), 100_004);
+	drop(writer);
+
+	let contents = fs::read(&path).unwrap();
+	assert_eq!(contents.len(), 100_004);
+	assert_eq!(&contents[..4], b"head");
+	assert!(contents[4..100_000].iter().all(|&b| b == 0));
+	assert_eq!(&contents[100_000..], b"tail");
+}
+
+#[test]
+fn seek_without_write_then_drop() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("unwritten");
+
+	let mut writer = unsafe { MmapMutFile::create(&path) }.unwrap().into_writer();
+	writer.write_all(b"kept").unwrap();
+	writer.seek(SeekFrom::Start(1 << 20)).unwrap();
+	assert_eq!(writer.len(), 4);
+	drop(writer);
+
+	// the high-water mark only moves on writes, so the seek leaves no trace in the file
+	assert_eq!(fs::read(&path).unwrap(), b"kept");
+}
+
+#[test]
+fn seek_relative_to_written_end() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("relative");
+
+	let mut writer = unsafe { MmapMutFile::create(&path) }.unwrap().into_writer();
+	writer.write_all(b"0123456789").unwrap();
+	assert_eq!(writer.seek(SeekFrom::End(-2)).unwrap(), 8);
+	assert_eq!(writer.seek(SeekFrom::Current(-3)).unwrap(), 5);
+	assert!(writer.seek(SeekFrom::Current(-6)).is_err());
+	assert_eq!(writer.position(), 5);
+}
+
+#[test]
+fn finish_maps_what_was_written() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("finished");
+
+	let mut writer = unsafe { MmapMutFile::create(&path) }.unwrap().into_writer();
+	writer.write_all(b"hello").unwrap();
+
+	let mut file = writer.finish().unwrap();
+	assert_eq!(&file[..], b"hello");
+	file[0] = b'j';
+	file.flush().unwrap();
+	drop(file);
+
+	assert_eq!(fs::read(&path).unwrap(), b"jello");
+}
+
+#[test]
+fn finish_read_only_maps_what_was_written() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("read_only");
+
+	let mut writer = unsafe { MmapMutFile::create(&path) }.unwrap().into_writer();
+	writer.write_all(b"hello world").unwrap();
+
+	let file = writer.finish_read_only().unwrap();
+	assert_eq!(file.map_len(), 11);
+
+	let mut contents = String::new();
+	file.into_reader().read_to_string(&mut contents).unwrap();
+	assert_eq!(contents, "hello world");
+}
+
+#[test]
+fn finish_without_writes_fails() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("empty");
+
+	let writer = unsafe { MmapMutFile::create(&path) }.unwrap().into_writer();
+	assert!(writer.finish().is_err());
+	assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+}
+
+#[test]
+fn flush_unaligned_offset_map() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("unaligned");
+	fs::write(&path, [0; 100]).unwrap();
+
+	let file = unsafe { MmapFile::options().read_write().offset(1).open(&path) }.unwrap();
+	file.flush_async().unwrap();
+	file.flush().unwrap();
+
+	let mut writer = file.into_writer();
+	writer.write_all(b"hello").unwrap();
+	writer.flush().unwrap();
+
+	let file = writer.finish().unwrap();
+	assert_eq!(&file[..], b"hello");
+	drop(file);
+
+	assert_eq!(&fs::read(&path).unwrap()[..6], b"\0hello");
+}
+
+#[test]
+fn write_vectored_grows_once_for_all_slices() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("vectored");
+
+	let file = unsafe { MmapMutFile::create_with_size(&path, 16) }.unwrap();
+	let mut writer = file.into_writer();
+
+	assert_eq!(writer.write_vectored(&[]).unwrap(), 0);
+	assert_eq!(writer.write_vectored(&[IoSlice::new(&[])]).unwrap(), 0);
+	assert_eq!(writer.len(), 0);
+
+	let big = vec![b'b'; 10_000];
+	let bufs = [
+		IoSlice::new(b"head"),
+		IoSlice::new(&[]),
+		IoSlice::new(&big),
+		IoSlice::new(&[]),
+		IoSlice::new(b"tail"),
+	];
+	assert_eq!(writer.write_vectored(&bufs).unwrap(), 10_008);
+	assert_eq!(writer.position(), 10_008);
+	assert_eq!(writer.len(), 10_008);
+
+	// empty slices after a seek back neither move the position nor the high-water mark
+	writer.seek(SeekFrom::Start(2)).unwrap();
+	assert_eq!(writer.write_vectored(&[IoSlice::new(&[])]).unwrap(), 0);
+	assert_eq!(writer.position(), 2);
+	assert_eq!(writer.len(), 10_008);
+	drop(writer);
+
+	let contents = fs::read(&path).unwrap();
+	assert_eq!(contents.len(), 10_008);
+	assert_eq!(&contents[..4], b"head");
+	assert_eq!(&contents[4..10_004], &big[..]);
+	assert_eq!(&contents[10_004..], b"tail");
+}